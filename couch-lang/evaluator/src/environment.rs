@@ -0,0 +1,96 @@
+use std::{cell::RefCell, collections::HashMap, rc::Rc};
+
+use crate::IdentifierType;
+
+#[derive(Default)]
+struct Frame {
+    definitions: HashMap<String, IdentifierType>,
+    slots: Vec<Option<IdentifierType>>,
+    parent: Option<Environment>,
+}
+
+#[derive(Clone, Default)]
+pub struct Environment {
+    frame: Rc<RefCell<Frame>>,
+}
+
+impl Environment {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn push(&self) -> Self {
+        Self {
+            frame: Rc::new(RefCell::new(Frame {
+                definitions: HashMap::new(),
+                slots: Vec::new(),
+                parent: Some(self.clone()),
+            })),
+        }
+    }
+    pub fn define(&self, name: String, definition: IdentifierType) {
+        self.frame.borrow_mut().definitions.insert(name, definition);
+    }
+    pub fn get(&self, name: &str) -> Option<IdentifierType> {
+        let frame = self.frame.borrow();
+        match frame.definitions.get(name) {
+            Some(definition) => Some(definition.clone()),
+            None => frame.parent.as_ref()?.get(name),
+        }
+    }
+    pub fn update<R>(&self, name: &str, f: impl FnOnce(&mut IdentifierType) -> R) -> Option<R> {
+        let mut frame = self.frame.borrow_mut();
+        if let Some(definition) = frame.definitions.get_mut(name) {
+            return Some(f(definition));
+        }
+        let parent = frame.parent.clone();
+        drop(frame);
+        parent?.update(name, f)
+    }
+    fn ancestor(&self, depth: usize) -> Option<Environment> {
+        let mut environment = self.clone();
+        for _ in 0..depth {
+            let parent = environment.frame.borrow().parent.clone()?;
+            environment = parent;
+        }
+        Some(environment)
+    }
+    pub fn define_slot(&self, slot: usize, definition: IdentifierType) {
+        let slots = &mut self.frame.borrow_mut().slots;
+        if slots.len() <= slot {
+            slots.resize(slot + 1, None);
+        }
+        slots[slot] = Some(definition);
+    }
+    pub fn get_slot(&self, depth: usize, slot: usize) -> Option<IdentifierType> {
+        self.ancestor(depth)?
+            .frame
+            .borrow()
+            .slots
+            .get(slot)?
+            .clone()
+    }
+    pub fn update_slot<R>(
+        &self,
+        depth: usize,
+        slot: usize,
+        f: impl FnOnce(&mut IdentifierType) -> R,
+    ) -> Option<R> {
+        let environment = self.ancestor(depth)?;
+        let mut frame = environment.frame.borrow_mut();
+        frame.slots.get_mut(slot)?.as_mut().map(f)
+    }
+    pub fn definitions(&self) -> Vec<(String, IdentifierType)> {
+        let mut definitions: Vec<_> = self
+            .frame
+            .borrow()
+            .definitions
+            .iter()
+            .map(|(name, definition)| (name.clone(), definition.clone()))
+            .collect();
+        definitions.sort_by(|(a, _), (b, _)| a.cmp(b));
+        definitions
+    }
+    pub fn clear(&self) {
+        self.frame.borrow_mut().definitions.clear();
+    }
+}
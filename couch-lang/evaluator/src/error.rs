@@ -0,0 +1,66 @@
+use std::fmt::Display;
+
+use couch_lang_parser::Position;
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ErrorKind {
+    Syntax,
+    UndefinedName,
+    Type,
+    Arithmetic,
+    Index,
+    Field,
+    Match,
+    Immutable,
+    Arity,
+    ControlFlow,
+    StackOverflow,
+    Native,
+    Exit(i32),
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::Syntax => f.write_str("syntax error"),
+            ErrorKind::UndefinedName => f.write_str("undefined name"),
+            ErrorKind::Type => f.write_str("type error"),
+            ErrorKind::Arithmetic => f.write_str("arithmetic error"),
+            ErrorKind::Index => f.write_str("index error"),
+            ErrorKind::Field => f.write_str("field error"),
+            ErrorKind::Match => f.write_str("match error"),
+            ErrorKind::Immutable => f.write_str("immutable binding"),
+            ErrorKind::Arity => f.write_str("arity error"),
+            ErrorKind::ControlFlow => f.write_str("control flow error"),
+            ErrorKind::StackOverflow => f.write_str("stack overflow"),
+            ErrorKind::Native => f.write_str("native error"),
+            ErrorKind::Exit(code) => write!(f, "exit with code {code}"),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct CallFrame {
+    pub name: String,
+    pub position: Position,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct RuntimeError {
+    pub kind: ErrorKind,
+    pub message: String,
+    pub position: Position,
+    pub backtrace: Vec<CallFrame>,
+}
+
+impl Display for RuntimeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Position { line, column, .. } = self.position;
+        write!(f, "{} at {line}:{column}: {}", self.kind, self.message)?;
+        for frame in &self.backtrace {
+            let Position { line, column, .. } = frame.position;
+            write!(f, "\n  in {} called at {line}:{column}", frame.name)?;
+        }
+        Ok(())
+    }
+}
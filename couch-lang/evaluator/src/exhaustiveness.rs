@@ -0,0 +1,295 @@
+use std::collections::HashMap;
+
+use couch_lang_parser::{Expression, MatchArm, Node, Pattern, Statement};
+
+use crate::{
+    environment::Environment,
+    error::{ErrorKind, RuntimeError},
+    IdentifierType,
+};
+
+static WILDCARD: Pattern = Pattern::Wildcard;
+
+pub(crate) fn check(statements: &[Node<Statement>], globals: &Environment) -> Vec<RuntimeError> {
+    let mut checker = Checker::default();
+    for (name, definition) in globals.definitions() {
+        if let IdentifierType::Enum { value } = definition {
+            checker.enums.insert(name, value.variants.clone());
+        }
+    }
+    for statement in statements {
+        checker.visit_statement(statement);
+    }
+    checker
+        .matches
+        .iter()
+        .filter_map(|node| checker.check_match(node))
+        .collect()
+}
+
+#[derive(Default)]
+struct Checker<'a> {
+    enums: HashMap<String, Vec<(String, usize)>>,
+    matches: Vec<&'a Node<Expression>>,
+}
+
+impl<'a> Checker<'a> {
+    fn visit_statement(&mut self, node: &'a Node<Statement>) {
+        match &node.value {
+            Statement::Function { body, .. } => self.visit_expression(body),
+            Statement::Let { value, .. } => self.visit_expression(value),
+            Statement::Return(value) => {
+                if let Some(value) = value {
+                    self.visit_expression(value);
+                }
+            }
+            Statement::While { condition, body } => {
+                self.visit_expression(condition);
+                self.visit_expression(body);
+            }
+            Statement::Impl { methods, .. } => {
+                for method in methods {
+                    self.visit_statement(method);
+                }
+            }
+            Statement::Enum {
+                identifier,
+                variants,
+            } => {
+                let Expression::Identifier(name) = &identifier.value else {
+                    return;
+                };
+                let variants = variants
+                    .iter()
+                    .filter_map(|(variant, fields)| match &variant.value {
+                        Expression::Identifier(variant) => Some((variant.clone(), fields.len())),
+                        _ => None,
+                    })
+                    .collect();
+                self.enums.insert(name.clone(), variants);
+            }
+            Statement::Assignment { left, right, .. } => {
+                self.visit_expression(left);
+                self.visit_expression(right);
+            }
+            Statement::Expression(expression) => self.visit_expression(expression),
+            Statement::Break
+            | Statement::Continue
+            | Statement::Struct { .. }
+            | Statement::Error(_) => {}
+        }
+    }
+    fn visit_expression(&mut self, node: &'a Node<Expression>) {
+        match &node.value {
+            Expression::Call { subject, arguments } => {
+                self.visit_expression(subject);
+                for argument in arguments {
+                    self.visit_expression(argument);
+                }
+            }
+            Expression::List(elements) => {
+                for element in elements {
+                    self.visit_expression(element);
+                }
+            }
+            Expression::Index { subject, index } => {
+                self.visit_expression(subject);
+                self.visit_expression(index);
+            }
+            Expression::Map(entries)
+            | Expression::Struct {
+                fields: entries, ..
+            } => {
+                for (key, value) in entries {
+                    self.visit_expression(key);
+                    self.visit_expression(value);
+                }
+            }
+            Expression::Member { subject, .. }
+            | Expression::Path { subject, .. }
+            | Expression::Unary { subject, .. } => self.visit_expression(subject),
+            Expression::Match { subject, arms } => {
+                self.matches.push(node);
+                self.visit_expression(subject);
+                for MatchArm { guard, body, .. } in arms {
+                    if let Some(guard) = guard {
+                        self.visit_expression(guard);
+                    }
+                    self.visit_expression(body);
+                }
+            }
+            Expression::Function { body, .. } => self.visit_expression(body),
+            Expression::Block(statements) => {
+                for statement in statements {
+                    self.visit_statement(statement);
+                }
+            }
+            Expression::If {
+                condition,
+                truthy,
+                falsy,
+            } => {
+                self.visit_expression(condition);
+                self.visit_expression(truthy);
+                if let Some(falsy) = falsy {
+                    self.visit_expression(falsy);
+                }
+            }
+            Expression::Binary { left, right, .. } => {
+                self.visit_expression(left);
+                self.visit_expression(right);
+            }
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::Bool(_)
+            | Expression::String(_)
+            | Expression::Identifier(_)
+            | Expression::Error(_) => {}
+        }
+    }
+    fn check_match(&self, node: &Node<Expression>) -> Option<RuntimeError> {
+        let Expression::Match { arms, .. } = &node.value else {
+            panic!("expected Match, got {:#?}", node.value);
+        };
+        if !arms.iter().all(|arm| self.known(&arm.pattern.value)) {
+            return None;
+        }
+        let rows: Vec<Vec<&Pattern>> = arms
+            .iter()
+            .filter(|arm| arm.guard.is_none())
+            .map(|arm| vec![&arm.pattern.value])
+            .collect();
+        let missing: Vec<String> = self
+            .missing(&rows, 1)
+            .into_iter()
+            .map(|mut witness| witness.remove(0))
+            .collect();
+        if missing.is_empty() {
+            return None;
+        }
+        Some(RuntimeError {
+            kind: ErrorKind::Match,
+            message: format!("missing match arms for {}", missing.join(", ")),
+            position: node.position.clone(),
+            backtrace: Vec::new(),
+        })
+    }
+    fn known(&self, pattern: &Pattern) -> bool {
+        match pattern {
+            Pattern::Variant {
+                variant, fields, ..
+            } => {
+                let arity = self.variants(pattern).and_then(|variants| {
+                    variants
+                        .iter()
+                        .find(|(name, _)| variant.value == Expression::Identifier(name.to_string()))
+                        .map(|(_, arity)| *arity)
+                });
+                arity == Some(fields.len()) && fields.iter().all(|field| self.known(&field.value))
+            }
+            Pattern::Error(_) => false,
+            Pattern::Wildcard | Pattern::Identifier(_) | Pattern::Literal(_) => true,
+        }
+    }
+    fn variants(&self, pattern: &Pattern) -> Option<&Vec<(String, usize)>> {
+        match pattern {
+            Pattern::Variant { enumeration, .. } => match &enumeration.value {
+                Expression::Identifier(name) => self.enums.get(name.as_str()),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+    fn constructors(&self, column: &[&Pattern]) -> Option<Vec<(String, usize)>> {
+        for pattern in column {
+            match pattern {
+                Pattern::Variant { enumeration, .. } => {
+                    let Expression::Identifier(enumeration) = &enumeration.value else {
+                        continue;
+                    };
+                    let variants = self.variants(pattern)?;
+                    return Some(
+                        variants
+                            .iter()
+                            .map(|(variant, arity)| (format!("{enumeration}::{variant}"), *arity))
+                            .collect(),
+                    );
+                }
+                Pattern::Literal(Expression::Bool(_)) => {
+                    return Some(vec![("true".to_string(), 0), ("false".to_string(), 0)])
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+    fn constructor(pattern: &Pattern) -> Option<(String, &[Node<Pattern>])> {
+        match pattern {
+            Pattern::Variant {
+                enumeration,
+                variant,
+                fields,
+            } => match (&enumeration.value, &variant.value) {
+                (Expression::Identifier(enumeration), Expression::Identifier(variant)) => {
+                    Some((format!("{enumeration}::{variant}"), fields))
+                }
+                _ => None,
+            },
+            Pattern::Literal(Expression::Bool(value)) => Some((value.to_string(), &[])),
+            _ => None,
+        }
+    }
+    fn missing(&self, rows: &[Vec<&'a Pattern>], width: usize) -> Vec<Vec<String>> {
+        if width == 0 {
+            return if rows.is_empty() {
+                vec![Vec::new()]
+            } else {
+                Vec::new()
+            };
+        }
+        let irrefutable =
+            |pattern: &Pattern| matches!(pattern, Pattern::Wildcard | Pattern::Identifier(_));
+        let column: Vec<&Pattern> = rows.iter().map(|row| row[0]).collect();
+        let Some(constructors) = self.constructors(&column) else {
+            let rows: Vec<Vec<&Pattern>> = rows
+                .iter()
+                .filter(|row| irrefutable(row[0]))
+                .map(|row| row[1..].to_vec())
+                .collect();
+            return self
+                .missing(&rows, width - 1)
+                .into_iter()
+                .map(|witness| [vec!["_".to_string()], witness].concat())
+                .collect();
+        };
+        let mut witnesses = Vec::new();
+        for (name, arity) in constructors {
+            let rows: Vec<Vec<&Pattern>> = rows
+                .iter()
+                .filter_map(|row| {
+                    let fields: Vec<&Pattern> = if irrefutable(row[0]) {
+                        vec![&WILDCARD; arity]
+                    } else {
+                        match Self::constructor(row[0]) {
+                            Some((other, fields)) if other == name => {
+                                fields.iter().map(|field| &field.value).collect()
+                            }
+                            _ => return None,
+                        }
+                    };
+                    Some([fields, row[1..].to_vec()].concat())
+                })
+                .collect();
+            for witness in self.missing(&rows, arity + width - 1) {
+                let (fields, rest) = witness.split_at(arity);
+                let pattern = if fields.is_empty() {
+                    name.clone()
+                } else {
+                    format!("{name}({})", fields.join(", "))
+                };
+                witnesses.push([vec![pattern], rest.to_vec()].concat());
+            }
+        }
+        witnesses
+    }
+}
@@ -1,334 +1,3953 @@
 use std::{
-    collections::HashMap,
-    ops::{Add, Div, Mul, Sub},
+    cell::{Cell, RefCell},
+    collections::BTreeMap,
+    io::{stdout, Write},
+    ops::{Add, BitAnd, BitOr, BitXor, Div, Mul, Rem, Shl, Shr, Sub},
+    rc::Rc,
 };
 
+#[derive(Clone)]
 pub enum IdentifierType {
     Value { mutable: bool, value: Value },
-    Function { value: Node<Statement> },
+    Function { value: Rc<Function> },
+    Struct { value: Rc<StructType> },
+    Enum { value: Rc<EnumType> },
 }
 
 use couch_lang_parser::{
-    AssignmentVariant, BinaryVariant, Expression, Node, Position, Statement, UnaryVariant,
+    AssignmentVariant, BinaryVariant, Expression, Node, Parameter, Pattern, Position, Statement,
+    UnaryVariant,
 };
 
+pub mod environment;
+pub mod error;
+mod exhaustiveness;
+mod prelude;
+pub mod resolver;
+mod syntax;
 pub mod value;
-use value::Value;
+use environment::Environment;
+use error::{CallFrame, ErrorKind, RuntimeError};
+use prelude::Output;
+use resolver::{Binding, Resolution};
+use value::{
+    EnumType, Function, Instance, List, Map, NativeFunction, OperationResult, Reference,
+    StructType, Value, Variant,
+};
+
+pub const MAX_CALL_DEPTH: usize = 128;
+pub const MAX_EVALUATION_DEPTH: usize = 2048;
+/// Evaluation moves onto a fresh `STACK_SEGMENT` once less than
+/// `STACK_RED_ZONE` is left, so the depth limits hold on any thread.
+const STACK_RED_ZONE: usize = 256 * 1024;
+const STACK_SEGMENT: usize = 4 * 1024 * 1024;
+
+fn method(object: &Value, member: &Node<Expression>) -> Option<Rc<Function>> {
+    match (object, &member.value) {
+        (Value::Struct(instance), Expression::Identifier(name)) => {
+            instance.definition.methods.borrow().get(name).cloned()
+        }
+        _ => None,
+    }
+}
+
+fn assignment_root(expression: &Node<Expression>) -> Option<(&Node<Expression>, &str)> {
+    match &expression.value {
+        Expression::Identifier(identifier) => Some((expression, identifier)),
+        Expression::Index { subject, .. } | Expression::Member { subject, .. } => {
+            assignment_root(subject)
+        }
+        _ => None,
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Outcome {
+    pub value: Value,
+    pub exit_code: i32,
+    pub diagnostics: Vec<RuntimeError>,
+}
+
+impl Outcome {
+    fn error(diagnostics: Vec<RuntimeError>) -> Self {
+        Self {
+            value: Value::Unit,
+            exit_code: 1,
+            diagnostics,
+        }
+    }
+}
+
+enum Place {
+    Element(List, i64),
+    Entry(Map, String),
+    Field(Reference<Instance>, usize),
+}
+
+impl Place {
+    fn locate(&self, inserting: bool) -> OperationResult<()> {
+        match self {
+            Place::Element(list, index) => {
+                let length = list.borrow().len();
+                match usize::try_from(*index) {
+                    Ok(index) if index < length => Ok(()),
+                    _ => Err((
+                        ErrorKind::Index,
+                        format!("index {index} out of bounds for list of length {length}"),
+                    )),
+                }
+            }
+            Place::Entry(map, key) => {
+                if inserting || map.borrow().contains_key(key) {
+                    Ok(())
+                } else {
+                    Err((ErrorKind::Index, format!("key {key:?} not found in map")))
+                }
+            }
+            Place::Field(..) => Ok(()),
+        }
+    }
+    fn read(&self) -> Value {
+        match self {
+            Place::Element(list, index) if !list.mutable() => {
+                list.borrow()[*index as usize].clone().frozen()
+            }
+            Place::Element(list, index) => list.borrow()[*index as usize].clone(),
+            Place::Entry(map, key) if !map.mutable() => map.borrow()[key].clone().frozen(),
+            Place::Entry(map, key) => map.borrow()[key].clone(),
+            Place::Field(instance, field) if !instance.mutable() => {
+                instance.fields.borrow()[*field].clone().frozen()
+            }
+            Place::Field(instance, field) => instance.fields.borrow()[*field].clone(),
+        }
+    }
+    fn write(&self, variant: &AssignmentVariant, value: Value) -> OperationResult<()> {
+        match self {
+            Place::Element(list, _) if !list.mutable() => Err((
+                ErrorKind::Immutable,
+                "cannot mutate immutable list".to_string(),
+            )),
+            Place::Element(list, index) => {
+                list.borrow_mut()[*index as usize].assign(variant, value)
+            }
+            Place::Entry(map, _) if !map.mutable() => Err((
+                ErrorKind::Immutable,
+                "cannot mutate immutable map".to_string(),
+            )),
+            Place::Entry(map, key) => map
+                .borrow_mut()
+                .entry(key.clone())
+                .or_insert(Value::Unit)
+                .assign(variant, value),
+            Place::Field(instance, _) if !instance.mutable() => Err((
+                ErrorKind::Immutable,
+                format!("cannot mutate immutable {}", instance.definition.name),
+            )),
+            Place::Field(instance, field) => {
+                instance.fields.borrow_mut()[*field].assign(variant, value)
+            }
+        }
+    }
+}
+
+enum Unwind {
+    Error(RuntimeError),
+    Return(Value),
+    Break(Position),
+    Continue(Position),
+    Exit { code: i32, position: Position },
+}
+
+pub struct Evaluator {
+    call_stack: Vec<CallFrame>,
+    depth: usize,
+    natives: Environment,
+    environment: Environment,
+    resolution: Rc<Resolution>,
+    output: Output,
+    exit_request: Rc<Cell<Option<i32>>>,
+}
 
-pub struct Evaluator {}
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
 impl Evaluator {
-    pub fn evaluate_statements(statements: Vec<Node<Statement>>) -> Option<Value> {
-        let mut outer_context = HashMap::new();
-        let mut inner_context = HashMap::new();
-        statements.into_iter().find_map(|statement| {
-            Self::evaluate_statement(statement, &mut outer_context, &mut inner_context)
+    pub fn new() -> Self {
+        let natives = Environment::new();
+        let output: Output = Rc::new(RefCell::new(Box::new(stdout())));
+        let exit_request = Rc::new(Cell::new(None));
+        let mut evaluator = Self {
+            call_stack: Vec::new(),
+            depth: 0,
+            environment: natives.push(),
+            natives,
+            resolution: Rc::default(),
+            output: Rc::clone(&output),
+            exit_request: Rc::clone(&exit_request),
+        };
+        prelude::register_prelude(&mut evaluator, &output, &exit_request);
+        evaluator
+    }
+    pub fn set_output<W: Write + 'static>(&mut self, output: W) {
+        *self.output.borrow_mut() = Box::new(output);
+    }
+    pub fn register<F>(&mut self, name: &str, arity: usize, function: F)
+    where
+        F: Fn(&[Value]) -> Result<Value, String> + 'static,
+    {
+        let native = NativeFunction {
+            name: name.to_string(),
+            arity,
+            function: Box::new(function),
+        };
+        self.natives.define(
+            name.to_string(),
+            IdentifierType::Value {
+                mutable: false,
+                value: Value::NativeFunction(Rc::new(native)),
+            },
+        );
+    }
+    pub fn evaluate_statements<W: Write + 'static>(
+        statements: Vec<Node<Statement>>,
+        output: W,
+    ) -> Outcome {
+        let mut evaluator = Self::new();
+        evaluator.set_output(output);
+        evaluator.run(&statements)
+    }
+    pub fn run(&mut self, statements: &[Node<Statement>]) -> Outcome {
+        let diagnostics = syntax::check(statements);
+        if !diagnostics.is_empty() {
+            return Outcome::error(diagnostics);
+        }
+        let diagnostics = exhaustiveness::check(statements, &self.environment);
+        if !diagnostics.is_empty() {
+            return Outcome::error(diagnostics);
+        }
+        match resolver::resolve(statements, &self.environment) {
+            Ok(resolution) => self.resolution = Rc::new(resolution),
+            Err(diagnostics) => return Outcome::error(diagnostics),
+        }
+
+        let environment = self.environment.clone();
+        let mut value = Value::Unit;
+        for statement in statements {
+            value = match self.execute(statement, &environment) {
+                Ok(result) => result.unwrap_or(Value::Unit),
+                Err(Unwind::Exit { code, .. }) => {
+                    return Outcome {
+                        value,
+                        exit_code: code,
+                        diagnostics: Vec::new(),
+                    }
+                }
+                Err(unwind) => match self.resolve(unwind) {
+                    Ok(value) => value,
+                    Err(error) => return Outcome::error(vec![error]),
+                },
+            };
+        }
+        Outcome {
+            value,
+            exit_code: 0,
+            diagnostics: Vec::new(),
+        }
+    }
+    pub fn definitions(&self) -> Vec<(String, IdentifierType)> {
+        self.environment.definitions()
+    }
+    pub fn natives(&self) -> Vec<(String, IdentifierType)> {
+        self.natives.definitions()
+    }
+    pub fn reset(&mut self) {
+        self.environment.clear();
+    }
+    fn runtime_error(&self, kind: ErrorKind, message: String, position: &Position) -> RuntimeError {
+        RuntimeError {
+            kind,
+            message,
+            position: position.clone(),
+            backtrace: self.call_stack.iter().rev().cloned().collect(),
+        }
+    }
+    fn error(&self, kind: ErrorKind, message: String, position: &Position) -> Unwind {
+        Unwind::Error(self.runtime_error(kind, message, position))
+    }
+    fn resolve(&self, unwind: Unwind) -> Result<Value, RuntimeError> {
+        Err(match unwind {
+            Unwind::Return(value) => return Ok(value),
+            Unwind::Error(error) => error,
+            Unwind::Break(position) => self.runtime_error(
+                ErrorKind::ControlFlow,
+                "break outside of loop".to_string(),
+                &position,
+            ),
+            Unwind::Continue(position) => self.runtime_error(
+                ErrorKind::ControlFlow,
+                "continue outside of loop".to_string(),
+                &position,
+            ),
+            Unwind::Exit { code, position } => self.runtime_error(
+                ErrorKind::Exit(code),
+                format!("exited with code {code}"),
+                &position,
+            ),
+        })
+    }
+    pub fn evaluate_statement(
+        &mut self,
+        statement: &Node<Statement>,
+        environment: &Environment,
+    ) -> Result<Option<Value>, RuntimeError> {
+        self.resolution = Rc::default();
+        self.execute(statement, environment)
+            .or_else(|unwind| self.resolve(unwind).map(Some))
+    }
+    fn lookup(
+        &self,
+        position: &Position,
+        name: &str,
+        environment: &Environment,
+    ) -> Option<IdentifierType> {
+        match self.resolution.binding(position) {
+            Some(Binding::Local { depth, slot }) => environment.get_slot(depth, slot),
+            Some(Binding::Global) | None => environment.get(name),
+        }
+    }
+    fn declare(
+        &self,
+        position: &Position,
+        name: &str,
+        definition: IdentifierType,
+        environment: &Environment,
+    ) {
+        let definition = match definition {
+            IdentifierType::Value {
+                mutable: false,
+                value,
+            } => IdentifierType::Value {
+                mutable: false,
+                value: value.frozen(),
+            },
+            definition => definition,
+        };
+        match self.resolution.binding(position) {
+            Some(Binding::Local { slot, .. }) => environment.define_slot(slot, definition),
+            Some(Binding::Global) | None => environment.define(name.to_string(), definition),
+        }
+    }
+    fn enter(&mut self, position: &Position) -> Result<(), Unwind> {
+        if self.depth >= MAX_EVALUATION_DEPTH {
+            return Err(self.error(
+                ErrorKind::StackOverflow,
+                format!("maximum evaluation depth of {MAX_EVALUATION_DEPTH} exceeded"),
+                position,
+            ));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+    fn execute(
+        &mut self,
+        node: &Node<Statement>,
+        environment: &Environment,
+    ) -> Result<Option<Value>, Unwind> {
+        self.enter(&node.position)?;
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || {
+            self.execute_statement(node, environment)
+        });
+        self.depth -= 1;
+        result
+    }
+    fn execute_statement(
+        &mut self,
+        node: &Node<Statement>,
+        environment: &Environment,
+    ) -> Result<Option<Value>, Unwind> {
+        match &node.value {
+            Statement::Function {
+                identifier,
+                parameters,
+                body,
+                ..
+            } => {
+                let Expression::Identifier(name) = &identifier.value else {
+                    return Err(self.error(
+                        ErrorKind::Syntax,
+                        format!("expected identifier, got {:#?}", identifier.value),
+                        &identifier.position,
+                    ));
+                };
+                let value = Rc::new(Function {
+                    name: Some(name.clone()),
+                    parameters: parameters.clone(),
+                    body: (**body).clone(),
+                    environment: environment.clone(),
+                    resolution: Rc::clone(&self.resolution),
+                });
+                self.declare(
+                    &identifier.position,
+                    name,
+                    IdentifierType::Function { value },
+                    environment,
+                );
+                Ok(None)
+            }
+            Statement::Let {
+                mutable,
+                identifier,
+                value,
+                ..
+            } => {
+                let Expression::Identifier(name) = &identifier.value else {
+                    return Err(self.error(
+                        ErrorKind::Syntax,
+                        format!("expected identifier, got {:#?}", identifier.value),
+                        &identifier.position,
+                    ));
+                };
+                let value = self.evaluate(value, environment)?;
+                self.declare(
+                    &identifier.position,
+                    name,
+                    IdentifierType::Value {
+                        mutable: *mutable,
+                        value,
+                    },
+                    environment,
+                );
+                Ok(None)
+            }
+            Statement::Return(value) => {
+                if self.call_stack.is_empty() {
+                    return Err(self.error(
+                        ErrorKind::ControlFlow,
+                        "return outside of function".to_string(),
+                        &node.position,
+                    ));
+                }
+                let value = match value {
+                    Some(value) => self.evaluate(value, environment)?,
+                    None => Value::Unit,
+                };
+                Err(Unwind::Return(value))
+            }
+            Statement::While { condition, body } => {
+                let Expression::Block(statements) = &body.value else {
+                    panic!("expected Block, got {:#?}", body.value);
+                };
+                loop {
+                    match self.evaluate(condition, environment)? {
+                        Value::Bool(true) => {}
+                        Value::Bool(false) => break Ok(None),
+                        value => {
+                            break Err(self.error(
+                                ErrorKind::Type,
+                                format!("expected bool, got {value}"),
+                                &condition.position,
+                            ))
+                        }
+                    };
+                    match self.evaluate_block(statements, &environment.push()) {
+                        Ok(_) | Err(Unwind::Continue(_)) => {}
+                        Err(Unwind::Break(_)) => break Ok(None),
+                        Err(unwind) => break Err(unwind),
+                    }
+                }
+            }
+            Statement::Struct { .. } => self.define_struct(node, environment),
+            Statement::Impl { .. } => self.define_methods(node, environment),
+            Statement::Enum { .. } => self.define_enum(node, environment),
+            Statement::Break => Err(Unwind::Break(node.position.clone())),
+            Statement::Continue => Err(Unwind::Continue(node.position.clone())),
+            Statement::Error(message) => {
+                Err(self.error(ErrorKind::Syntax, message.clone(), &node.position))
+            }
+            Statement::Assignment { .. } => self.evaluate_assignment(node, environment),
+            Statement::Expression(expression) => Ok(Some(self.evaluate(expression, environment)?)),
+        }
+    }
+    fn define_struct(
+        &mut self,
+        node: &Node<Statement>,
+        environment: &Environment,
+    ) -> Result<Option<Value>, Unwind> {
+        let Statement::Struct { identifier, fields } = &node.value else {
+            panic!("expected Struct, got {:#?}", node.value);
+        };
+        let name = self.identifier_name(identifier)?;
+        let mut names: Vec<String> = Vec::new();
+        for field in fields {
+            let field_name = self.identifier_name(field)?;
+            if names.contains(&field_name) {
+                return Err(self.error(
+                    ErrorKind::Field,
+                    format!("field `{field_name}` declared more than once on {name}"),
+                    &field.position,
+                ));
+            }
+            names.push(field_name);
+        }
+        let value = Rc::new(StructType {
+            name: name.clone(),
+            fields: names,
+            methods: RefCell::default(),
+        });
+        self.declare(
+            &identifier.position,
+            &name,
+            IdentifierType::Struct { value },
+            environment,
+        );
+        Ok(None)
+    }
+    fn define_enum(
+        &mut self,
+        node: &Node<Statement>,
+        environment: &Environment,
+    ) -> Result<Option<Value>, Unwind> {
+        let Statement::Enum {
+            identifier,
+            variants,
+        } = &node.value
+        else {
+            panic!("expected Enum, got {:#?}", node.value);
+        };
+        let name = self.identifier_name(identifier)?;
+        let mut definitions: Vec<(String, usize)> = Vec::new();
+        for (variant, fields) in variants {
+            let variant_name = self.identifier_name(variant)?;
+            if definitions.iter().any(|(other, _)| *other == variant_name) {
+                return Err(self.error(
+                    ErrorKind::Field,
+                    format!("variant `{variant_name}` declared more than once on {name}"),
+                    &variant.position,
+                ));
+            }
+            definitions.push((variant_name, fields.len()));
+        }
+        let value = Rc::new(EnumType {
+            name: name.clone(),
+            variants: definitions,
+        });
+        self.declare(
+            &identifier.position,
+            &name,
+            IdentifierType::Enum { value },
+            environment,
+        );
+        Ok(None)
+    }
+    fn define_methods(
+        &mut self,
+        node: &Node<Statement>,
+        environment: &Environment,
+    ) -> Result<Option<Value>, Unwind> {
+        let Statement::Impl {
+            identifier,
+            methods,
+        } = &node.value
+        else {
+            panic!("expected Impl, got {:#?}", node.value);
+        };
+        let definition = self.struct_type(identifier, environment)?;
+        for method in methods {
+            let Statement::Function {
+                identifier,
+                parameters,
+                body,
+                ..
+            } = &method.value
+            else {
+                panic!("expected Function, got {:#?}", method.value);
+            };
+            let name = self.identifier_name(identifier)?;
+            let takes_self = matches!(
+                parameters.first().map(|parameter| &parameter.value),
+                Some(Parameter::Item { identifier, .. })
+                    if identifier.value == Expression::Identifier("self".to_string())
+            );
+            if !takes_self {
+                return Err(self.error(
+                    ErrorKind::Type,
+                    format!(
+                        "method {name} on {} must take self as its first parameter",
+                        definition.name
+                    ),
+                    &method.position,
+                ));
+            }
+            let value = Rc::new(Function {
+                name: Some(format!("{}.{name}", definition.name)),
+                parameters: parameters.clone(),
+                body: (**body).clone(),
+                environment: environment.clone(),
+                resolution: Rc::clone(&self.resolution),
+            });
+            definition.methods.borrow_mut().insert(name, value);
+        }
+        Ok(None)
+    }
+    fn evaluate_assignment(
+        &mut self,
+        node: &Node<Statement>,
+        environment: &Environment,
+    ) -> Result<Option<Value>, Unwind> {
+        let Statement::Assignment {
+            left,
+            right,
+            variant,
+        } = &node.value
+        else {
+            panic!("expected Assignment, got {:#?}", node.value);
+        };
+        let Some((root, identifier)) = assignment_root(left) else {
+            return Err(self.error(
+                ErrorKind::Syntax,
+                format!("expected identifier or index, got {:#?}", left.value),
+                &left.position,
+            ));
+        };
+        self.check_mutable(root, identifier, environment, &node.position)?;
+
+        if let Expression::Identifier(_) = &left.value {
+            let right = self.evaluate(right, environment)?;
+            let assign = |definition: &mut IdentifierType| match definition {
+                IdentifierType::Value { value, .. } => value.assign(variant, right),
+                IdentifierType::Function { .. }
+                | IdentifierType::Struct { .. }
+                | IdentifierType::Enum { .. } => {
+                    unreachable!("checked above")
+                }
+            };
+            return match self.resolution.binding(&left.position) {
+                Some(Binding::Local { depth, slot }) => {
+                    environment.update_slot(depth, slot, assign)
+                }
+                Some(Binding::Global) | None => environment.update(identifier, assign),
+            }
+            .expect("checked above")
+            .map(|()| None)
+            .map_err(|(kind, message)| self.error(kind, message, &node.position));
+        }
+
+        let (place, key_position) = self.evaluate_place(left, environment)?;
+        let right = self.evaluate(right, environment)?;
+        place
+            .locate(*variant == AssignmentVariant::Base)
+            .map_err(|(kind, message)| self.error(kind, message, key_position))?;
+        place
+            .write(variant, right)
+            .map(|()| None)
+            .map_err(|(kind, message)| self.error(kind, message, &node.position))
+    }
+    fn identifier_name(&self, node: &Node<Expression>) -> Result<String, Unwind> {
+        match &node.value {
+            Expression::Identifier(name) => Ok(name.clone()),
+            value => Err(self.error(
+                ErrorKind::Syntax,
+                format!("expected identifier, got {value:#?}"),
+                &node.position,
+            )),
+        }
+    }
+    fn struct_type(
+        &self,
+        identifier: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Rc<StructType>, Unwind> {
+        let name = self.identifier_name(identifier)?;
+        let (kind, message) = match self.lookup(&identifier.position, &name, environment) {
+            Some(IdentifierType::Struct { value }) => return Ok(value),
+            Some(_) => (
+                ErrorKind::Type,
+                format!("identifier {name} is not a struct"),
+            ),
+            None => (
+                ErrorKind::UndefinedName,
+                format!("identifier {name} not defined"),
+            ),
+        };
+        Err(self.error(kind, message, &identifier.position))
+    }
+    fn enum_type(
+        &self,
+        identifier: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Rc<EnumType>, Unwind> {
+        let name = self.identifier_name(identifier)?;
+        let (kind, message) = match self.lookup(&identifier.position, &name, environment) {
+            Some(IdentifierType::Enum { value }) => return Ok(value),
+            Some(_) => (ErrorKind::Type, format!("identifier {name} is not an enum")),
+            None => (
+                ErrorKind::UndefinedName,
+                format!("identifier {name} not defined"),
+            ),
+        };
+        Err(self.error(kind, message, &identifier.position))
+    }
+    fn variant_index(
+        &self,
+        definition: &EnumType,
+        variant: &Node<Expression>,
+    ) -> Result<usize, Unwind> {
+        let name = self.identifier_name(variant)?;
+        definition
+            .variants
+            .iter()
+            .position(|(other, _)| *other == name)
+            .ok_or_else(|| {
+                self.error(
+                    ErrorKind::Field,
+                    format!("no variant `{name}` on {}", definition.name),
+                    &variant.position,
+                )
+            })
+    }
+    fn evaluate_path(
+        &mut self,
+        expression: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Value, Unwind> {
+        let Expression::Path { subject, member } = &expression.value else {
+            panic!("expected Path, got {:#?}", expression.value);
+        };
+        let definition = self.enum_type(subject, environment)?;
+        let variant = self.variant_index(&definition, member)?;
+        let (name, arity) = &definition.variants[variant];
+        if *arity == 0 {
+            return Ok(Value::Enum(Rc::new(Variant {
+                definition,
+                variant,
+                fields: Vec::new(),
+            })));
+        }
+        let native = NativeFunction {
+            name: format!("{}::{name}", definition.name),
+            arity: *arity,
+            function: Box::new(move |arguments| {
+                Ok(Value::Enum(Rc::new(Variant {
+                    definition: Rc::clone(&definition),
+                    variant,
+                    fields: arguments.to_vec(),
+                })))
+            }),
+        };
+        Ok(Value::NativeFunction(Rc::new(native)))
+    }
+    fn evaluate_match(
+        &mut self,
+        expression: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Value, Unwind> {
+        let Expression::Match { subject, arms } = &expression.value else {
+            panic!("expected Match, got {:#?}", expression.value);
+        };
+        let value = self.evaluate(subject, environment)?;
+        for arm in arms {
+            let scope = environment.push();
+            if !self.match_pattern(&arm.pattern, &value, &scope)? {
+                continue;
+            }
+            if let Some(guard) = &arm.guard {
+                match self.evaluate(guard, &scope)? {
+                    Value::Bool(true) => {}
+                    Value::Bool(false) => continue,
+                    value => {
+                        return Err(self.error(
+                            ErrorKind::Type,
+                            format!("expected bool, got {value}"),
+                            &guard.position,
+                        ))
+                    }
+                }
+            }
+            return self.evaluate(&arm.body, &scope);
+        }
+        Err(self.error(
+            ErrorKind::Match,
+            format!("no match arm matched {}", value.to_printable()),
+            &expression.position,
+        ))
+    }
+    fn match_pattern(
+        &self,
+        pattern: &Node<Pattern>,
+        value: &Value,
+        scope: &Environment,
+    ) -> Result<bool, Unwind> {
+        match &pattern.value {
+            Pattern::Wildcard => Ok(true),
+            Pattern::Identifier(name) => {
+                self.declare(
+                    &pattern.position,
+                    name,
+                    IdentifierType::Value {
+                        mutable: false,
+                        value: value.clone(),
+                    },
+                    scope,
+                );
+                Ok(true)
+            }
+            Pattern::Literal(literal) => Ok(match (literal, value) {
+                (Expression::Integer(literal), Value::Integer(value)) => literal == value,
+                (Expression::Float(literal), Value::Float(value)) => literal == value,
+                (Expression::Bool(literal), Value::Bool(value)) => literal == value,
+                (Expression::String(literal), Value::String(value)) => literal == value,
+                _ => false,
+            }),
+            Pattern::Variant {
+                enumeration,
+                variant,
+                fields,
+            } => {
+                let definition = self.enum_type(enumeration, scope)?;
+                let index = self.variant_index(&definition, variant)?;
+                let (name, arity) = &definition.variants[index];
+                if fields.len() != *arity {
+                    return Err(self.error(
+                        ErrorKind::Arity,
+                        format!(
+                            "variant {}::{name} has {arity} fields, pattern has {}",
+                            definition.name,
+                            fields.len()
+                        ),
+                        &pattern.position,
+                    ));
+                }
+                let Value::Enum(value) = value else {
+                    return Ok(false);
+                };
+                if !Rc::ptr_eq(&value.definition, &definition) || value.variant != index {
+                    return Ok(false);
+                }
+                for (field, value) in fields.iter().zip(&value.fields) {
+                    if !self.match_pattern(field, value, scope)? {
+                        return Ok(false);
+                    }
+                }
+                Ok(true)
+            }
+            Pattern::Error(message) => {
+                Err(self.error(ErrorKind::Syntax, message.clone(), &pattern.position))
+            }
+        }
+    }
+    fn evaluate_struct_literal(
+        &mut self,
+        expression: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Value, Unwind> {
+        let Expression::Struct { identifier, fields } = &expression.value else {
+            panic!("expected Struct, got {:#?}", expression.value);
+        };
+        let definition = self.struct_type(identifier, environment)?;
+        let mut values: Vec<Option<Value>> = vec![None; definition.fields.len()];
+        for (field, value) in fields {
+            let name = self.identifier_name(field)?;
+            let Some(index) = definition.fields.iter().position(|other| *other == name) else {
+                return Err(self.error(
+                    ErrorKind::Field,
+                    format!("no field `{name}` on {}", definition.name),
+                    &field.position,
+                ));
+            };
+            if values[index].is_some() {
+                return Err(self.error(
+                    ErrorKind::Field,
+                    format!("field `{name}` specified more than once"),
+                    &field.position,
+                ));
+            }
+            values[index] = Some(self.evaluate(value, environment)?);
+        }
+        let fields = values
+            .into_iter()
+            .zip(&definition.fields)
+            .map(|(value, name)| {
+                value.ok_or_else(|| {
+                    self.error(
+                        ErrorKind::Field,
+                        format!("missing field `{name}` in {} literal", definition.name),
+                        &expression.position,
+                    )
+                })
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Value::Struct(Reference::new(Instance {
+            definition,
+            fields: RefCell::new(fields),
+        })))
+    }
+    fn check_mutable(
+        &self,
+        root: &Node<Expression>,
+        identifier: &str,
+        environment: &Environment,
+        position: &Position,
+    ) -> Result<(), Unwind> {
+        let (kind, message) = match self.lookup(&root.position, identifier, environment) {
+            Some(IdentifierType::Value { mutable: true, .. }) => return Ok(()),
+            Some(IdentifierType::Value { .. }) => (
+                ErrorKind::Immutable,
+                format!("identifier {identifier} is not mutable"),
+            ),
+            Some(IdentifierType::Function { .. }) => (
+                ErrorKind::Immutable,
+                "function definitions are not mutable".to_string(),
+            ),
+            Some(IdentifierType::Struct { .. }) => (
+                ErrorKind::Immutable,
+                "struct definitions are not mutable".to_string(),
+            ),
+            Some(IdentifierType::Enum { .. }) => (
+                ErrorKind::Immutable,
+                "enum definitions are not mutable".to_string(),
+            ),
+            None => (
+                ErrorKind::UndefinedName,
+                format!("identifier {identifier} not defined"),
+            ),
+        };
+        Err(self.error(kind, message, position))
+    }
+    fn evaluate_place<'a>(
+        &mut self,
+        target: &'a Node<Expression>,
+        environment: &Environment,
+    ) -> Result<(Place, &'a Position), Unwind> {
+        match &target.value {
+            Expression::Index { subject, index } => {
+                let subject_value = self.evaluate(subject, environment)?;
+                let key = self.evaluate(index, environment)?;
+                let place = match (subject_value, key) {
+                    (Value::List(list), Value::Integer(index)) => Place::Element(list, index),
+                    (Value::List(_), key) => {
+                        return Err(self.error(
+                            ErrorKind::Type,
+                            format!("list index must be integer, got {key}"),
+                            &index.position,
+                        ))
+                    }
+                    (Value::Map(map), Value::String(key)) => Place::Entry(map, key),
+                    (Value::Map(_), key) => {
+                        return Err(self.error(
+                            ErrorKind::Type,
+                            format!("map key must be string, got {key}"),
+                            &index.position,
+                        ))
+                    }
+                    (subject_value, _) => {
+                        return Err(self.error(
+                            ErrorKind::Type,
+                            format!("cannot index into {subject_value}"),
+                            &subject.position,
+                        ))
+                    }
+                };
+                Ok((place, &index.position))
+            }
+            Expression::Member { subject, member } => {
+                let object = self.evaluate(subject, environment)?;
+                self.member_place(object, member)
+            }
+            value => panic!("expected Index or Member, got {value:#?}"),
+        }
+    }
+    fn member_place<'a>(
+        &self,
+        object: Value,
+        member: &'a Node<Expression>,
+    ) -> Result<(Place, &'a Position), Unwind> {
+        let Expression::Identifier(name) = &member.value else {
+            panic!("expected Identifier, got {:#?}", member.value);
+        };
+        match object {
+            Value::Map(map) => Ok((Place::Entry(map, name.clone()), &member.position)),
+            Value::Struct(instance) => {
+                let Some(field) = instance
+                    .definition
+                    .fields
+                    .iter()
+                    .position(|field| field == name)
+                else {
+                    return Err(self.error(
+                        ErrorKind::Field,
+                        format!("no field `{name}` on {}", instance.definition.name),
+                        &member.position,
+                    ));
+                };
+                Ok((Place::Field(instance, field), &member.position))
+            }
+            value => Err(self.error(
+                ErrorKind::Type,
+                format!("cannot access member {name} on {value}"),
+                &member.position,
+            )),
+        }
+    }
+    fn read_member(&self, object: Value, member: &Node<Expression>) -> Result<Value, Unwind> {
+        let (place, position) = self.member_place(object, member)?;
+        place
+            .locate(false)
+            .map_err(|(kind, message)| self.error(kind, message, position))?;
+        Ok(place.read())
+    }
+    fn evaluate_block(
+        &mut self,
+        statements: &[Node<Statement>],
+        environment: &Environment,
+    ) -> Result<Option<Value>, Unwind> {
+        let mut last = None;
+        for statement in statements {
+            last = self.execute(statement, environment)?;
+        }
+        Ok(last)
+    }
+    fn evaluate_binary_expression(
+        &mut self,
+        node: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Value, Unwind> {
+        let Expression::Binary {
+            left,
+            right,
+            variant,
+        } = &node.value
+        else {
+            panic!("expected Binary, got {:#?}", node.value);
+        };
+
+        let left_position = &left.position;
+        let left = self.evaluate(left, environment)?;
+
+        if let BinaryVariant::And | BinaryVariant::Or = variant {
+            match (variant, left) {
+                (BinaryVariant::And, Value::Bool(false)) => return Ok(Value::Bool(false)),
+                (BinaryVariant::Or, Value::Bool(true)) => return Ok(Value::Bool(true)),
+                (_, Value::Bool(_)) => {}
+                (_, value) => {
+                    return Err(self.error(
+                        ErrorKind::Type,
+                        format!("expected bool, got {value}"),
+                        left_position,
+                    ))
+                }
+            };
+            return match self.evaluate(right, environment)? {
+                value @ Value::Bool(_) => Ok(value),
+                value => Err(self.error(
+                    ErrorKind::Type,
+                    format!("expected bool, got {value}"),
+                    &right.position,
+                )),
+            };
+        }
+
+        let right = self.evaluate(right, environment)?;
+
+        macro_rules! impl_variant {
+            ($func_name:ident) => {
+                left.$func_name(right)
+                    .map_err(|(kind, message)| self.error(kind, message, &node.position))?
+            };
+        }
+
+        Ok(match variant {
+            BinaryVariant::Addition => impl_variant!(add),
+            BinaryVariant::Subtraction => impl_variant!(sub),
+            BinaryVariant::Multiplication => impl_variant!(mul),
+            BinaryVariant::Division => impl_variant!(div),
+            BinaryVariant::Modulo => impl_variant!(rem),
+            BinaryVariant::Exponentiation => impl_variant!(pow),
+            BinaryVariant::BitwiseAnd => impl_variant!(bitand),
+            BinaryVariant::BitwiseOr => impl_variant!(bitor),
+            BinaryVariant::BitwiseXor => impl_variant!(bitxor),
+            BinaryVariant::LeftShift => impl_variant!(shl),
+            BinaryVariant::RightShift => impl_variant!(shr),
+            BinaryVariant::Equal => Value::Bool(left == right),
+            BinaryVariant::NotEqual => Value::Bool(left != right),
+            BinaryVariant::Less => impl_variant!(less),
+            BinaryVariant::LessEqual => impl_variant!(less_equal),
+            BinaryVariant::Greater => impl_variant!(greater),
+            BinaryVariant::GreaterEqual => impl_variant!(greater_equal),
+            BinaryVariant::And | BinaryVariant::Or => unreachable!("short-circuited above"),
         })
     }
-    fn evaluate_statement(
-        node: Node<Statement>,
-        outer_context: &mut HashMap<String, IdentifierType>,
-        inner_context: &mut HashMap<String, IdentifierType>,
-    ) -> Option<Value> {
-        match node.value {
-            Statement::Let {
-                mutable,
-                identifier,
-                value,
-            } => {
-                let Expression::Identifier(identifier) = identifier.value else {
-                    return Some(Value::Error { message: format!("expected identifier, got {:#?}", identifier.value), line: identifier.position.line, column: identifier.position.column });
-                };
-                let value = Self::evaluate_expression(*value, outer_context, inner_context);
-                inner_context.insert(identifier, IdentifierType::Value { mutable, value });
-                None
-            }
-            Statement::Return(_) => todo!("return statement"),
-            Statement::Error(message) => Some(Value::Error {
-                message,
-                line: node.position.line,
-                column: node.position.column,
-            }),
-            Statement::Assignment {
-                left,
-                right,
-                variant,
-            } => {
-                let Expression::Identifier(identifier) = left.value else {
-                    return Some(Value::Error { message: format!("expected identifier, got {:#?}", left.value), line: left.position.line, column: left.position.column });
-                };
-                let right = Evaluator::evaluate_expression(*right, outer_context, inner_context);
-                let Some(identifier_ref) = inner_context
-                    .get_mut(&identifier)
-                    .or(outer_context.get_mut(&identifier)) else {
-                        return Some(Value::Error { message: format!("identifier {identifier} not defined"), line: node.position.line, column: node.position.column });
-                };
-                match identifier_ref {
-                    IdentifierType::Value { mutable, value } => {
-                        if *mutable {
-                            match variant {
-                                AssignmentVariant::Base => *value = right,
-                                AssignmentVariant::Addition => *value += right,
-                                AssignmentVariant::Subtraction => *value -= right,
-                                AssignmentVariant::Multiplication => *value *= right,
-                                AssignmentVariant::Division => *value /= right,
-                            };
-                            None
-                        } else {
-                            Some(Value::Error {
-                                message: format!("identifier {identifier} is not mutable"),
-                                line: node.position.line,
-                                column: node.position.column,
-                            })
-                        }
-                    }
-                    IdentifierType::Function { .. } => Some(Value::Error {
-                        message: format!("function definitions are not mutable"),
-                        line: node.position.line,
-                        column: node.position.column,
-                    }),
-                }
-            }
-            Statement::Expression(expression) => Some(Self::evaluate_expression(
-                expression,
-                outer_context,
-                inner_context,
-            )),
+    fn evaluate_native_call(
+        &mut self,
+        native: &NativeFunction,
+        node: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Value, Unwind> {
+        let Expression::Call { arguments, .. } = &node.value else {
+            panic!("expected Call, got {:#?}", node.value);
+        };
+
+        if native.arity != arguments.len() {
+            return Err(self.error(
+                ErrorKind::Arity,
+                format!(
+                    "function {} takes {} arguments, got {}",
+                    native.name,
+                    native.arity,
+                    arguments.len()
+                ),
+                &node.position,
+            ));
+        }
+
+        let mut values = Vec::with_capacity(arguments.len());
+        for argument in arguments {
+            values.push(self.evaluate(argument, environment)?);
+        }
+        let result = (native.function)(&values);
+        if let Some(code) = self.exit_request.take() {
+            return Err(Unwind::Exit {
+                code,
+                position: node.position.clone(),
+            });
+        }
+        result.map_err(|message| self.error(ErrorKind::Native, message, &node.position))
+    }
+    fn evaluate_callee(
+        &mut self,
+        node: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<(Value, Option<Value>), Unwind> {
+        let Expression::Call { subject, .. } = &node.value else {
+            panic!("expected Call, got {:#?}", node.value);
+        };
+        let callee = match &subject.value {
+            Expression::Identifier(name) => match self.lookup(&subject.position, name, environment)
+            {
+                Some(IdentifierType::Function { value }) => Value::Function(value),
+                Some(IdentifierType::Value {
+                    value: value @ (Value::Function(_) | Value::NativeFunction(_)),
+                    ..
+                }) => value,
+                Some(IdentifierType::Value { value, .. }) => {
+                    return Err(self.error(
+                        ErrorKind::Type,
+                        format!("identifier {name} is {value}, not a function"),
+                        &node.position,
+                    ))
+                }
+                Some(IdentifierType::Struct { .. }) => {
+                    return Err(self.error(
+                        ErrorKind::Type,
+                        format!("identifier {name} is a struct, not a function"),
+                        &node.position,
+                    ))
+                }
+                Some(IdentifierType::Enum { .. }) => {
+                    return Err(self.error(
+                        ErrorKind::Type,
+                        format!("identifier {name} is an enum, not a function"),
+                        &node.position,
+                    ))
+                }
+                None => {
+                    return Err(self.error(
+                        ErrorKind::UndefinedName,
+                        format!("identifier {name} not defined"),
+                        &node.position,
+                    ))
+                }
+            },
+            Expression::Member {
+                subject: object_node,
+                member,
+            } => {
+                let object = self.evaluate(object_node, environment)?;
+                match method(&object, member) {
+                    Some(method) => {
+                        if let Some(Parameter::Item { mutable: true, .. }) =
+                            method.parameters.first().map(|parameter| &parameter.value)
+                        {
+                            if let Some((root, identifier)) = assignment_root(object_node) {
+                                self.check_mutable(root, identifier, environment, &node.position)?;
+                            }
+                            if let Value::Struct(instance) = &object {
+                                if !instance.mutable() {
+                                    return Err(self.error(
+                                        ErrorKind::Immutable,
+                                        format!(
+                                            "cannot mutate immutable {}",
+                                            instance.definition.name
+                                        ),
+                                        &node.position,
+                                    ));
+                                }
+                            }
+                        }
+                        return Ok((Value::Function(method), Some(object)));
+                    }
+                    None => self.read_member(object, member)?,
+                }
+            }
+            _ => self.evaluate(subject, environment)?,
+        };
+        Ok((callee, None))
+    }
+    fn evaluate_call(
+        &mut self,
+        node: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Value, Unwind> {
+        let Expression::Call { subject, arguments } = &node.value else {
+            panic!("expected Call, got {:#?}", node.value);
+        };
+
+        let (callee, receiver) = self.evaluate_callee(node, environment)?;
+        let function = match callee {
+            Value::Function(function) => function,
+            Value::NativeFunction(native) => {
+                return self.evaluate_native_call(&native, node, environment)
+            }
+            value => {
+                return Err(self.error(
+                    ErrorKind::Type,
+                    format!("expected function, got {value}"),
+                    &subject.position,
+                ))
+            }
+        };
+        let name = match (&function.name, &subject.value) {
+            (Some(name), _) | (None, Expression::Identifier(name)) => name.clone(),
+            (None, _) => "anonymous function".to_string(),
+        };
+        let Function {
+            parameters, body, ..
+        } = function.as_ref();
+
+        let mut values: Vec<Value> = receiver.into_iter().collect();
+        let arity = parameters.len() - values.len();
+        if arity != arguments.len() {
+            return Err(self.error(
+                ErrorKind::Arity,
+                format!(
+                    "function {name} takes {arity} arguments, got {}",
+                    arguments.len()
+                ),
+                &node.position,
+            ));
+        }
+
+        for argument in arguments {
+            values.push(self.evaluate(argument, environment)?);
+        }
+
+        if self.call_stack.len() >= MAX_CALL_DEPTH {
+            let outermost = &self.call_stack[0];
+            return Err(self.error(
+                ErrorKind::StackOverflow,
+                format!(
+                    "maximum call depth of {MAX_CALL_DEPTH} exceeded, starting from call to {} at {}:{}",
+                    outermost.name, outermost.position.line, outermost.position.column
+                ),
+                &node.position,
+            ));
+        }
+
+        let Expression::Block(statements) = &body.value else {
+            panic!("expected Block, got {:#?}", body.value);
+        };
+
+        let caller = std::mem::replace(&mut self.resolution, Rc::clone(&function.resolution));
+        let scope = function.environment.push();
+        for (parameter, value) in parameters.iter().zip(values) {
+            let Parameter::Item {
+                mutable,
+                identifier,
+                ..
+            } = &parameter.value
+            else {
+                panic!("expected Item, got {:#?}", parameter.value);
+            };
+            let Expression::Identifier(name) = &identifier.value else {
+                panic!("expected Identifier, got {:#?}", identifier.value);
+            };
+            self.declare(
+                &identifier.position,
+                name,
+                IdentifierType::Value {
+                    mutable: *mutable,
+                    value,
+                },
+                &scope,
+            );
+        }
+
+        self.call_stack.push(CallFrame {
+            name,
+            position: node.position.clone(),
+        });
+        let value = match self.evaluate_block(statements, &scope) {
+            Ok(_) => Ok(Value::Unit),
+            Err(unwind @ (Unwind::Error(_) | Unwind::Exit { .. })) => Err(unwind),
+            Err(unwind) => self.resolve(unwind).map_err(Unwind::Error),
+        };
+        self.call_stack.pop();
+        self.resolution = caller;
+        value
+    }
+    fn evaluate_if(
+        &mut self,
+        node: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Value, Unwind> {
+        let Expression::If {
+            condition,
+            truthy,
+            falsy,
+        } = &node.value
+        else {
+            panic!("expected If, got {:#?}", node.value);
+        };
+
+        match self.evaluate(condition, environment)? {
+            Value::Bool(true) => self.evaluate(truthy, environment),
+            Value::Bool(false) => match falsy {
+                Some(falsy) => self.evaluate(falsy, environment),
+                None => Ok(Value::Unit),
+            },
+            value => Err(self.error(
+                ErrorKind::Type,
+                format!("expected bool, got {value}"),
+                &condition.position,
+            )),
+        }
+    }
+    pub fn evaluate_expression(
+        &mut self,
+        expression: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Value, RuntimeError> {
+        self.resolution = Rc::default();
+        self.evaluate(expression, environment)
+            .or_else(|unwind| self.resolve(unwind))
+    }
+    fn evaluate(
+        &mut self,
+        expression: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Value, Unwind> {
+        self.enter(&expression.position)?;
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || {
+            self.evaluate_node(expression, environment)
+        });
+        self.depth -= 1;
+        result
+    }
+    fn evaluate_node(
+        &mut self,
+        expression: &Node<Expression>,
+        environment: &Environment,
+    ) -> Result<Value, Unwind> {
+        Ok(match &expression.value {
+            Expression::Integer(v) => Value::Integer(*v),
+            Expression::Float(v) => Value::Float(*v),
+            Expression::Bool(v) => Value::Bool(*v),
+            Expression::String(v) => Value::String(v.clone()),
+            Expression::Unary { subject, variant } => {
+                let evaluated_subject = self.evaluate(subject, environment)?;
+                match (variant, evaluated_subject) {
+                    (UnaryVariant::NegateNumber, Value::Integer(v)) => match v.checked_neg() {
+                        Some(v) => Value::Integer(v),
+                        None => {
+                            return Err(self.error(
+                                ErrorKind::Arithmetic,
+                                "integer overflow".to_string(),
+                                &expression.position,
+                            ))
+                        }
+                    },
+                    (UnaryVariant::NegateNumber, Value::Float(v)) => Value::Float(-v),
+                    (UnaryVariant::NegateBool, Value::Bool(v)) => Value::Bool(!v),
+                    (UnaryVariant::NegateBool, v) => {
+                        return Err(self.error(
+                            ErrorKind::Type,
+                            format!("expected bool, got {v:#?}"),
+                            &expression.position,
+                        ))
+                    }
+                    (UnaryVariant::NegateNumber, v) => {
+                        return Err(self.error(
+                            ErrorKind::Type,
+                            format!("expected number, got {v:#?}"),
+                            &expression.position,
+                        ))
+                    }
+                }
+            }
+            Expression::Binary { .. } => {
+                self.evaluate_binary_expression(expression, environment)?
+            }
+            Expression::Call { .. } => self.evaluate_call(expression, environment)?,
+            Expression::If { .. } => self.evaluate_if(expression, environment)?,
+            Expression::Function {
+                parameters, body, ..
+            } => Value::Function(Rc::new(Function {
+                name: None,
+                parameters: parameters.clone(),
+                body: (**body).clone(),
+                environment: environment.clone(),
+                resolution: Rc::clone(&self.resolution),
+            })),
+            Expression::List(elements) => {
+                let mut values = Vec::with_capacity(elements.len());
+                for element in elements {
+                    values.push(self.evaluate(element, environment)?);
+                }
+                Value::List(Reference::new(RefCell::new(values)))
+            }
+            Expression::Map(entries) => {
+                let mut values = BTreeMap::new();
+                for (key, value) in entries {
+                    let key_value = match self.evaluate(key, environment)? {
+                        Value::String(key_value) => key_value,
+                        key_value => {
+                            return Err(self.error(
+                                ErrorKind::Type,
+                                format!("map key must be string, got {key_value}"),
+                                &key.position,
+                            ))
+                        }
+                    };
+                    values.insert(key_value, self.evaluate(value, environment)?);
+                }
+                Value::Map(Reference::new(RefCell::new(values)))
+            }
+            Expression::Index { .. } | Expression::Member { .. } => {
+                let (place, position) = self.evaluate_place(expression, environment)?;
+                place
+                    .locate(false)
+                    .map_err(|(kind, message)| self.error(kind, message, position))?;
+                place.read()
+            }
+            Expression::Struct { .. } => self.evaluate_struct_literal(expression, environment)?,
+            Expression::Path { .. } => self.evaluate_path(expression, environment)?,
+            Expression::Match { .. } => self.evaluate_match(expression, environment)?,
+            Expression::Block(statements) => self
+                .evaluate_block(statements, &environment.push())?
+                .unwrap_or(Value::Unit),
+            Expression::Identifier(q) => match self.lookup(&expression.position, q, environment) {
+                Some(IdentifierType::Value { value, .. }) => value.clone(),
+                Some(IdentifierType::Function { value }) => Value::Function(value),
+                Some(IdentifierType::Struct { .. }) => {
+                    return Err(self.error(
+                        ErrorKind::Type,
+                        format!("struct {q} is not a value"),
+                        &expression.position,
+                    ))
+                }
+                Some(IdentifierType::Enum { .. }) => {
+                    return Err(self.error(
+                        ErrorKind::Type,
+                        format!("enum {q} is not a value"),
+                        &expression.position,
+                    ))
+                }
+                None => {
+                    return Err(self.error(
+                        ErrorKind::UndefinedName,
+                        format!("identifier {q} is not yet given value"),
+                        &expression.position,
+                    ))
+                }
+            },
+            Expression::Error(message) => {
+                return Err(self.error(ErrorKind::Syntax, message.clone(), &expression.position))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::sink;
+
+    use couch_lang_lexer::Lexer;
+    use couch_lang_parser::{Parser, MAX_NESTING_DEPTH};
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn add_expression() {
+        let input = String::from("2 + 4");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+        let environment = Environment::new();
+        let value = Evaluator::new()
+            .evaluate_expression(&expression, &environment)
+            .unwrap();
+        assert_eq!(
+            6,
+            match value {
+                Value::Integer(v) => v,
+                value => panic!("expected Integer, got {value:#?}"),
+            }
+        )
+    }
+
+    #[test]
+    fn identifier() {
+        let input = String::from("a");
+        let lexer = Lexer::new(input.chars());
+
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+
+        let environment = Environment::new();
+        environment.define(
+            "a".to_string(),
+            IdentifierType::Value {
+                value: Value::Integer(5),
+                mutable: false,
+            },
+        );
+        let value = Evaluator::new()
+            .evaluate_expression(&expression, &environment)
+            .unwrap();
+        assert_eq!(
+            5,
+            match value {
+                Value::Integer(v) => v,
+                value => panic!("expected Integer, got {value:#?}"),
+            }
+        )
+    }
+
+    #[test]
+    fn equals_expression() {
+        let input = String::from("2 == 4");
+        let lexer = Lexer::new(input.chars());
+
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+
+        let environment = Environment::new();
+        let value = Evaluator::new()
+            .evaluate_expression(&expression, &environment)
+            .unwrap();
+        assert_eq!(
+            false,
+            match value {
+                Value::Bool(v) => v,
+                value => panic!("expected Bool, got {value:#?}"),
+            }
+        )
+    }
+
+    #[test]
+    fn not_equals_expression() {
+        let input = String::from("2 != 4");
+        let lexer = Lexer::new(input.chars());
+
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+
+        let environment = Environment::new();
+        let value = Evaluator::new()
+            .evaluate_expression(&expression, &environment)
+            .unwrap();
+        assert_eq!(
+            true,
+            match value {
+                Value::Bool(v) => v,
+                value => panic!("expected Bool, got {value:#?}"),
+            }
+        )
+    }
+
+    #[test]
+    fn add_float_and_int_should_fail() {
+        let input = String::from("2.5 + 4");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+        let environment = Environment::new();
+        let error = Evaluator::new().evaluate_expression(&expression, &environment);
+        assert_eq!(
+            Err(RuntimeError {
+                kind: ErrorKind::Type,
+                message: "no implementation exists for float + integer".to_string(),
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+                backtrace: vec![],
+            }),
+            error
+        );
+    }
+
+    #[test]
+    fn evaluate_let_chain() {
+        let input = String::from("let mut a = 5; a += 5; a;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(10), outcome.value);
+    }
+
+    #[test]
+    fn function_declaration() {
+        let input = String::from("fn f(a, mut b) { return a; } let c = 5; c;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(5), outcome.value);
+    }
+
+    #[test]
+    fn assign_to_function_should_fail() {
+        let input = String::from("fn f() {} f = 5;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Immutable,
+                message: "function definitions are not mutable".to_string(),
+                position: Position {
+                    index: 10,
+                    line: 1,
+                    column: 11,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn call_function() {
+        let input = String::from("fn add(a, b) { return a + b; } add(2, 4);");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(6), outcome.value);
+    }
+
+    #[test]
+    fn call_function_with_mutable_parameter() {
+        let input = String::from("fn f(mut a) { a += 2; return a; } f(1);");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(3), outcome.value);
+    }
+
+    #[test]
+    fn assign_to_immutable_parameter_should_fail() {
+        let input = String::from("fn f(a) { a = 2; } f(1);");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Immutable,
+                message: "identifier a is not mutable".to_string(),
+                position: Position {
+                    index: 10,
+                    line: 1,
+                    column: 11,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn call_with_wrong_argument_count_should_fail() {
+        let input = String::from("fn f(a) { return a; } f(1, 2);");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Arity,
+                message: "function f takes 1 arguments, got 2".to_string(),
+                position: Position {
+                    index: 22,
+                    line: 1,
+                    column: 23,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn deeply_nested_recursion_should_fail() {
+        let function = "fn f(n) {\n    if n > 0 { { {\n        let mut i = 0;\n        while i < 1 {\n            i += 1;\n            match n { 0 => 0, _ => { { f(n - 1); } } };\n        }\n    } } }\n}\n";
+        for (call, expected) in [
+            ("f(40);", vec![]),
+            ("f(100000);", vec![ErrorKind::StackOverflow]),
+        ] {
+            let input = format!("{function}{call}");
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(
+                expected,
+                outcome
+                    .diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic.kind)
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn deeply_nested_source_is_a_syntax_error() {
+        let program = |depth: usize| {
+            format!(
+                "let x = {}1;{};\nlet y = {}1;\nx + y;",
+                "{ ".repeat(depth),
+                " }".repeat(depth),
+                "-".repeat(depth * 2)
+            )
+        };
+        for (depth, expected) in [(MAX_NESTING_DEPTH / 2 - 1, vec![]), (5000, vec![1, 2])] {
+            let input = program(depth);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(
+                expected,
+                outcome
+                    .diagnostics
+                    .iter()
+                    .map(|diagnostic| {
+                        assert_eq!(
+                            diagnostic.message,
+                            format!("maximum nesting depth of {MAX_NESTING_DEPTH} exceeded")
+                        );
+                        diagnostic.position.line
+                    })
+                    .collect::<Vec<_>>()
+            );
+        }
+    }
+
+    #[test]
+    fn deeply_nested_blocks_should_fail() {
+        let depth = 40;
+        let input = format!(
+            "fn f() {{ {}f();{} }}\nf();",
+            "{ ".repeat(depth),
+            " }".repeat(depth)
+        );
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        let (exit_code, diagnostics) = (outcome.exit_code, outcome.diagnostics);
+        assert_eq!(1, exit_code);
+        assert_eq!(
+            vec![format!(
+                "maximum evaluation depth of {MAX_EVALUATION_DEPTH} exceeded"
+            )],
+            diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.message.clone())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn unbounded_recursion_should_fail() {
+        let input = String::from("fn f() { f(); } f();");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        let recursive_call = CallFrame {
+            name: "f".to_string(),
+            position: Position {
+                index: 9,
+                line: 1,
+                column: 10,
+            },
+        };
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::StackOverflow,
+                message: format!(
+                    "maximum call depth of {MAX_CALL_DEPTH} exceeded, starting from call to f at 1:17"
+                ),
+                position: Position {
+                    index: 9,
+                    line: 1,
+                    column: 10,
+                },
+                backtrace: [
+                    vec![recursive_call; MAX_CALL_DEPTH - 1],
+                    vec![CallFrame {
+                        name: "f".to_string(),
+                        position: Position {
+                            index: 16,
+                            line: 1,
+                            column: 17,
+                        },
+                    }],
+                ]
+                .concat(),
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn runtime_error_backtrace() {
+        let input = String::from("fn inner() { return 1 / 0; }\nfn outer() { inner(); }\nouter();");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        let error = RuntimeError {
+            kind: ErrorKind::Arithmetic,
+            message: "division by zero".to_string(),
+            position: Position {
+                index: 20,
+                line: 1,
+                column: 21,
+            },
+            backtrace: vec![
+                CallFrame {
+                    name: "inner".to_string(),
+                    position: Position {
+                        index: 42,
+                        line: 2,
+                        column: 14,
+                    },
+                },
+                CallFrame {
+                    name: "outer".to_string(),
+                    position: Position {
+                        index: 53,
+                        line: 3,
+                        column: 1,
+                    },
+                },
+            ],
+        };
+        assert_eq!(
+            error.to_string(),
+            "arithmetic error at 1:21: division by zero\n  in inner called at 2:14\n  in outer called at 3:1"
+        );
+        assert_eq!(outcome.diagnostics, vec![error]);
+    }
+
+    #[test]
+    fn evaluate_statement_returns_result() {
+        let input = String::from("exit(3);");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let mut evaluator = Evaluator::new();
+        let environment = evaluator.environment.clone();
+        let error = evaluator
+            .evaluate_statement(&statements[0], &environment)
+            .unwrap_err();
+        assert_eq!(error.kind, ErrorKind::Exit(3));
+    }
+
+    #[test]
+    fn return_exits_early() {
+        let input = String::from("fn f() { return 1; let a = 1 / 0; } f();");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(1), outcome.value);
+    }
+
+    #[test]
+    fn return_without_value_is_unit() {
+        let input = String::from("fn f() { 5; return; } f();");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Unit, outcome.value);
+    }
+
+    #[test]
+    fn function_body_without_return_is_unit() {
+        let cases = [
+            ("fn f() { 5; } f();", Value::Unit),
+            ("fn f(x) { if x { 1; } else { 2; } } f(true);", Value::Unit),
+            ("let f = fn() { { 5; } }; f();", Value::Unit),
+            ("{ 5; }", Value::Integer(5)),
+            ("if true { 1; } else { 2; }", Value::Integer(1)),
+        ];
+        for (input, expected) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(Vec::<RuntimeError>::new(), outcome.diagnostics, "{input}");
+            assert_eq!(expected, outcome.value, "{input}");
+        }
+    }
+
+    #[test]
+    fn return_from_nested_calls() {
+        let input =
+            String::from("fn g(a) { return a * 2; } fn f() { let a = g(2); return a + 1; } f();");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(5), outcome.value);
+    }
+
+    #[test]
+    fn return_outside_function_should_fail() {
+        let input = String::from("let a = 1;\nreturn a;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::ControlFlow,
+                message: "return outside of function".to_string(),
+                position: Position {
+                    index: 11,
+                    line: 2,
+                    column: 1,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn while_loop() {
+        let input =
+            String::from("let mut i = 0; let mut sum = 0; while i != 5 { i += 1; sum += i; } sum;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(15), outcome.value);
+    }
+
+    #[test]
+    fn while_loop_break_and_continue() {
+        let input = String::from(
+            "let mut i = 0; while i != 10 { i += 1; break; } let mut j = 0; while j != 3 { j += 1; continue; i += 100; } i + j;",
+        );
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(4), outcome.value);
+    }
+
+    #[test]
+    fn return_from_while_loop() {
+        let input = String::from(
+            "fn f() { let mut i = 0; while i != 10 { i += 1; return i; } return 0; } f();",
+        );
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(1), outcome.value);
+    }
+
+    #[test]
+    fn while_condition_must_be_bool() {
+        let input = String::from("while 1 { }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Type,
+                message: "expected bool, got integer".to_string(),
+                position: Position {
+                    index: 6,
+                    line: 1,
+                    column: 7,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn break_outside_loop_should_fail() {
+        let input = String::from("let a = 1;\nbreak;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::ControlFlow,
+                message: "break outside of loop".to_string(),
+                position: Position {
+                    index: 11,
+                    line: 2,
+                    column: 1,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn continue_in_function_called_from_loop_should_fail() {
+        let input = String::from("fn f() { continue; } while 1 == 1 { f(); }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::ControlFlow,
+                message: "continue outside of loop".to_string(),
+                position: Position {
+                    index: 9,
+                    line: 1,
+                    column: 10,
+                },
+                backtrace: vec![CallFrame {
+                    name: "f".to_string(),
+                    position: Position {
+                        index: 36,
+                        line: 1,
+                        column: 37,
+                    },
+                }],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn if_else_expression() {
+        let input = String::from(
+            "let a = if 1 == 2 { 1; } else if true { let b = 2; b * 3; } else { 3; }; a;",
+        );
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(6), outcome.value);
+    }
+
+    #[test]
+    fn if_without_else_is_unit() {
+        let input = String::from("let a = if false { 1; }; a;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Unit, outcome.value);
+    }
+
+    #[test]
+    fn break_and_return_inside_if() {
+        let input = String::from(
+            "fn f() { let mut i = 0; while true { i += 1; if i == 5 { break; } } while true { if i == 8 { return i; } i += 1; } } f();",
+        );
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(8), outcome.value);
+    }
+
+    #[test]
+    fn if_condition_must_be_bool() {
+        let input = String::from("if 1.5 { 1; } else { 2; }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Type,
+                message: "expected bool, got float".to_string(),
+                position: Position {
+                    index: 3,
+                    line: 1,
+                    column: 4,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn string_concatenation() {
+        let input = String::from(r#"let mut a = "foo" + "\u{e6}"; a += "\"bar\""; a;"#);
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::String("fooæ\"bar\"".to_string()), outcome.value);
+    }
+
+    #[test]
+    fn string_equality() {
+        let input = String::from(r#""ab" == "a" + "b";"#);
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Bool(true), outcome.value);
+    }
+
+    #[test]
+    fn subtract_strings_should_fail() {
+        let input = String::from(r#""ab" - "b";"#);
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Type,
+                message: "no implementation exists for string - string".to_string(),
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn left_associative_arithmetic() {
+        let input = String::from("(10 - 3 - 2) + (8 / 4 / 2) * (2 + 3 * 4 - 6 / 2);");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(16), outcome.value);
+    }
+
+    #[test]
+    fn comparison_operators() {
+        let input = String::from("1 < 2 && 2 <= 2 && 3.5 > 1.5 && 2.0 >= 2.5 == false;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Bool(true), outcome.value);
+    }
+
+    #[test]
+    fn logical_operators_short_circuit() {
+        let input = String::from("false && 1 / 0 == 0 || true || 1 / 0 == 0;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Bool(true), outcome.value);
+    }
+
+    #[test]
+    fn compare_integer_and_float_should_fail() {
+        let input = String::from("1 < 2.0;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Type,
+                message: "no implementation exists for integer < float".to_string(),
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn logical_operator_on_integer_should_fail() {
+        let input = String::from("true && 1;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Type,
+                message: "expected bool, got integer".to_string(),
+                position: Position {
+                    index: 8,
+                    line: 1,
+                    column: 9,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn modulo_takes_sign_of_dividend() {
+        let input = String::from("let mut a = -7 % 3; a %= 2; a * 10 + 7 % -3;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(-9), outcome.value);
+    }
+
+    #[test]
+    fn exponentiation() {
+        let input = String::from("2 ** 3 ** 2 - (-2) ** 3;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(520), outcome.value);
+    }
+
+    #[test]
+    fn negative_exponent_should_fail() {
+        let input = String::from("2 ** -1;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Arithmetic,
+                message: "cannot raise integer to negative power -1".to_string(),
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn bitwise_operators() {
+        let input =
+            String::from("(12 & 10) + (12 | 10) * 100 + (12 ^ 10) * 10000 + (-1 & 255) * 1000000;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(255_061_408), outcome.value);
+    }
+
+    #[test]
+    fn shifts_out_of_range() {
+        let cases = [
+            ("1 << 4;", Value::Integer(16)),
+            ("-16 >> 2;", Value::Integer(-4)),
+            ("1 << 64;", Value::Integer(0)),
+            ("5 >> 100;", Value::Integer(0)),
+            ("-5 >> 100;", Value::Integer(-1)),
+        ];
+        for (input, expected) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(expected, outcome.value, "{input}");
+        }
+    }
+
+    #[test]
+    fn negative_shift_should_fail() {
+        let input = String::from("1 << -1;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Arithmetic,
+                message: "cannot shift by negative amount -1".to_string(),
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn division_by_zero_should_fail() {
+        let cases = [
+            "1 / 0;",
+            "1 % 0;",
+            "let mut a = 1; a /= 0;",
+            "let mut a = 1; a %= 0;",
+        ];
+        for input in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            let [RuntimeError { message, .. }] = outcome.diagnostics.as_slice() else {
+                panic!("expected error for {input}, got {outcome:#?}");
+            };
+            assert_eq!(message, "division by zero", "{input}");
+        }
+    }
+
+    #[test]
+    fn integer_overflow_should_fail() {
+        let input = String::from("let mut a = 9223372036854775807;\na += 1;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Arithmetic,
+                message: "integer overflow".to_string(),
+                position: Position {
+                    index: 33,
+                    line: 2,
+                    column: 1,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+
+        let cases = [
+            "9223372036854775807 + 1;",
+            "-9223372036854775807 - 2;",
+            "4611686018427387904 * 2;",
+            "(-9223372036854775807 - 1) / -1;",
+            "-(-9223372036854775807 - 1);",
+            "2 ** 63;",
+        ];
+        for input in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            let [RuntimeError { message, .. }] = outcome.diagnostics.as_slice() else {
+                panic!("expected error for {input}, got {outcome:#?}");
+            };
+            assert_eq!(message, "integer overflow", "{input}");
+        }
+    }
+
+    #[test]
+    fn remainder_of_minimum_by_minus_one() {
+        let cases = [
+            "(-9223372036854775807 - 1) % -1;",
+            "let mut a = -9223372036854775807 - 1; a %= -1; a;",
+        ];
+        for input in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(Vec::<RuntimeError>::new(), outcome.diagnostics, "{input}");
+            assert_eq!(Value::Integer(0), outcome.value, "{input}");
+        }
+    }
+
+    #[test]
+    fn numeric_conversions() {
+        let cases = [
+            ("int(2.9);", Value::Integer(2)),
+            ("int(-2.9);", Value::Integer(-2)),
+            ("int(7);", Value::Integer(7)),
+            ("float(3) / 2.0;", Value::Float(1.5)),
+            ("let mut c = 8; c += int(0.5); c;", Value::Integer(8)),
+            ("float(9007199254740993);", Value::Float(9007199254740992.0)),
+            ("1.0 / 0.0 > 1000000.0;", Value::Bool(true)),
+        ];
+        for (input, expected) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(expected, outcome.value, "{input}");
+        }
+    }
+
+    #[test]
+    fn invalid_numeric_conversions_should_fail() {
+        let cases = [
+            ("int(0.0 / 0.0);", "cannot convert NaN to integer"),
+            ("int(1.0 / 0.0);", "float inf is out of range for integer"),
+            (
+                "int(10000000000000000000.0);",
+                "float 10000000000000000000 is out of range for integer",
+            ),
+            ("float(true);", "cannot convert bool to float"),
+            ("int(1, 2);", "function int takes 1 arguments, got 2"),
+            (
+                "let mut c = 8; c += 0.5;",
+                "no implementation exists for integer += float",
+            ),
+        ];
+        for (input, expected) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            let [RuntimeError { message, .. }] = outcome.diagnostics.as_slice() else {
+                panic!("expected error for {input}, got {outcome:#?}");
+            };
+            assert_eq!(message, expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn run_keeps_definitions_between_calls() {
+        let mut evaluator = Evaluator::new();
+        for (input, expected) in [
+            ("let mut a = 1; fn double(x) { return x * 2; }", Value::Unit),
+            ("a += 2;", Value::Unit),
+            ("double(a);", Value::Integer(6)),
+        ] {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            assert_eq!(evaluator.run(&statements).value, expected, "{input}");
+        }
+        let names: Vec<String> = evaluator
+            .definitions()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["a", "double"]);
+
+        evaluator.reset();
+        assert!(evaluator.definitions().is_empty());
+        let input = String::from("a;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        assert_eq!(
+            evaluator.run(&statements).diagnostics,
+            vec![RuntimeError {
+                kind: ErrorKind::UndefinedName,
+                message: "identifier a is not yet given value".to_string(),
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+                backtrace: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn environment_scope_chain() {
+        let global = Environment::new();
+        let value = |value| IdentifierType::Value {
+            mutable: true,
+            value: Value::Integer(value),
+        };
+        let lookup = |environment: &Environment, name: &str| match environment.get(name) {
+            Some(IdentifierType::Value { value, .. }) => Some(value),
+            _ => None,
+        };
+        global.define("a".to_string(), value(1));
+        global.define("b".to_string(), value(2));
+
+        let inner = global.push();
+        inner.define("a".to_string(), value(10));
+        assert_eq!(lookup(&inner, "a"), Some(Value::Integer(10)));
+        assert_eq!(lookup(&inner, "b"), Some(Value::Integer(2)));
+
+        inner.update("b", |definition| *definition = value(20));
+        assert_eq!(lookup(&global, "b"), Some(Value::Integer(20)));
+        assert_eq!(inner.definitions().len(), 1);
+
+        assert_eq!(lookup(&inner.push(), "a"), Some(Value::Integer(10)));
+        assert_eq!(lookup(&global, "a"), Some(Value::Integer(1)));
+        assert_eq!(inner.update("c", |_| ()), None);
+
+        let slots = inner.push();
+        slots.define_slot(1, value(30));
+        inner.define_slot(0, value(40));
+        let lookup_slot = |depth, slot| match slots.get_slot(depth, slot) {
+            Some(IdentifierType::Value { value, .. }) => Some(value),
+            _ => None,
+        };
+        assert_eq!(lookup_slot(0, 1), Some(Value::Integer(30)));
+        assert_eq!(lookup_slot(0, 0), None);
+        assert_eq!(lookup_slot(1, 0), Some(Value::Integer(40)));
+        slots.update_slot(1, 0, |definition| *definition = value(50));
+        assert_eq!(lookup_slot(1, 0), Some(Value::Integer(50)));
+        assert_eq!(lookup(&slots, "a"), Some(Value::Integer(10)));
+        assert_eq!(slots.update_slot(0, 0, |_| ()), None);
+    }
+
+    #[test]
+    fn block_evaluates_to_last_expression() {
+        let input = String::from("let a = { let b = 2; b * 3; }; { a + 1; }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(7), outcome.value);
+    }
+
+    #[test]
+    fn block_like_statements_end_without_semicolon() {
+        let cases = [
+            (
+                "{ let a = 1; }\n[0, 1];",
+                list(vec![Value::Integer(0), Value::Integer(1)]),
+            ),
+            ("{ println(\"block\"); }\n(1 + 2);", Value::Integer(3)),
+            ("if true { 1; }\n-1;", Value::Integer(-1)),
+            ("match 1 { _ => { 1; } }\n-2;", Value::Integer(-2)),
+        ];
+        for (input, expected) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(outcome.diagnostics, vec![], "{input}");
+            assert_eq!(expected, outcome.value, "{input}");
+        }
+    }
+
+    #[test]
+    fn block_scope_is_dropped_at_closing_brace() {
+        let input =
+            String::from("let mut a = 1; let c = { let a = 10; a; }; let d = { a += c; }; a;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(11), outcome.value);
+
+        let input = String::from("let a = { let b = 2; };\nb;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::UndefinedName,
+                message: "identifier b is not yet given value".to_string(),
+                position: Position {
+                    index: 24,
+                    line: 2,
+                    column: 1,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn functions_as_values() {
+        let input = String::from(
+            "fn apply(f, x) { return f(x); }
+            fn square(x) { return x * x; }
+            let double = fn(x) { return x * 2; };
+            apply(square, 3) + apply(double, 4) + fn(x) { return x; }(100);",
+        );
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(117), outcome.value);
+    }
+
+    #[test]
+    fn closures_capture_environment_by_reference() {
+        let input = String::from(
+            "fn make_counter() {
+                let mut count = 0;
+                return fn() { count += 1; return count; };
+            }
+            let counter = make_counter();
+            let other = make_counter();
+            let first = counter();
+            let second = counter();
+            let third = other();
+            let mut base = 10;
+            let add_base = fn(x) { return x + base; };
+            base = 20;
+            counter() * 100 + add_base(1);",
+        );
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(Value::Integer(321), outcome.value);
+    }
+
+    #[test]
+    fn call_non_function_should_fail() {
+        let input = String::from("(1 + 2)(3);");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(
+            vec![RuntimeError {
+                kind: ErrorKind::Type,
+                message: "expected function, got integer".to_string(),
+                position: Position {
+                    index: 1,
+                    line: 1,
+                    column: 2,
+                },
+                backtrace: vec![],
+            }],
+            outcome.diagnostics
+        );
+    }
+
+    #[test]
+    fn native_functions() {
+        let mut evaluator = Evaluator::new();
+        evaluator.register("add", 2, |arguments| match arguments {
+            [Value::Integer(a), Value::Integer(b)] => Ok(Value::Integer(a + b)),
+            [a, b] => Err(format!("cannot add {a} and {b}")),
+            _ => unreachable!("arity is checked by the evaluator"),
+        });
+        let cases = [
+            ("add(1, 2);", Ok(Value::Integer(3))),
+            (
+                "fn apply(f) { return f(3, 4); } apply(add);",
+                Ok(Value::Integer(7)),
+            ),
+            (
+                "add(1);",
+                Err(RuntimeError {
+                    kind: ErrorKind::Arity,
+                    message: "function add takes 2 arguments, got 1".to_string(),
+                    position: Position {
+                        index: 0,
+                        line: 1,
+                        column: 1,
+                    },
+                    backtrace: vec![],
+                }),
+            ),
+            (
+                "\n  add(1, true);",
+                Err(RuntimeError {
+                    kind: ErrorKind::Native,
+                    message: "cannot add integer and bool".to_string(),
+                    position: Position {
+                        index: 3,
+                        line: 2,
+                        column: 3,
+                    },
+                    backtrace: vec![],
+                }),
+            ),
+        ];
+        for (input, expected) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = evaluator.run(&statements);
+            match expected {
+                Ok(value) => assert_eq!(outcome.value, value, "{input}"),
+                Err(diagnostic) => assert_eq!(outcome.diagnostics, vec![diagnostic], "{input}"),
+            }
+        }
+        assert!(evaluator
+            .definitions()
+            .iter()
+            .all(|(name, _)| name != "add"));
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn prelude_print_writes_to_output() {
+        let input = String::from(
+            "let a = print(\"a = \"); let b = println(1 + 2); let c = println(0.5);
+            let d = print(true); print(type_of);",
+        );
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let buffer = SharedBuffer::default();
+        let mut evaluator = Evaluator::new();
+        evaluator.set_output(buffer.clone());
+        assert_eq!(evaluator.run(&statements).value, Value::Unit);
+        assert_eq!(
+            String::from_utf8(buffer.0.borrow().clone()).unwrap(),
+            "a = 3\n0.5\ntrue<function type_of>"
+        );
+
+        let input = String::from("println(\"to output\");");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let buffer = SharedBuffer::default();
+        let outcome = Evaluator::evaluate_statements(statements, buffer.clone());
+        assert_eq!(Value::Unit, outcome.value);
+        assert_eq!(
+            String::from_utf8(buffer.0.borrow().clone()).unwrap(),
+            "to output\n"
+        );
+    }
+
+    #[test]
+    fn prelude_builtins() {
+        let cases = [
+            (
+                "type_of(1) + type_of(1.0) + type_of(\"\") + type_of(min);",
+                Value::String("integerfloatstringfunction".to_string()),
+            ),
+            ("min(3, -2) * 10 + max(3, -2);", Value::Integer(-17)),
+            ("min(0.5, 1.5) + max(0.5, 1.5);", Value::Float(2.0)),
+            ("abs(-4) + int(abs(-1.5));", Value::Integer(5)),
+            ("sqrt(16.0) + floor(-1.5);", Value::Float(2.0)),
+            (
+                "let a = assert(1 < 2); assert_eq(\"a\" + \"b\", \"ab\");",
+                Value::Unit,
+            ),
+        ];
+        for (input, expected) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(expected, outcome.value, "{input}");
+        }
+    }
+
+    #[test]
+    fn prelude_builtin_errors() {
+        let cases = [
+            ("assert(1 > 2);", "assertion failed"),
+            ("assert(1);", "expected bool, got integer"),
+            ("assert_eq(1, 1.0);", "assertion failed: 1 != 1.0"),
+            (
+                "min(1, 2.0);",
+                "no implementation exists for min(integer, float)",
+            ),
+            ("abs(-9223372036854775807 - 1);", "integer overflow"),
+            ("sqrt(4);", "expected float, got integer"),
+            ("floor(\"1.5\");", "expected float, got string"),
+        ];
+        for (input, expected) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(
+                vec![RuntimeError {
+                    kind: ErrorKind::Native,
+                    message: expected.to_string(),
+                    position: Position {
+                        index: 0,
+                        line: 1,
+                        column: 1,
+                    },
+                    backtrace: vec![],
+                }],
+                outcome.diagnostics,
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn expression_statements_do_not_stop_execution() {
+        let input = String::from(
+            "fn f() { println(\"f\"); }
+            fn g() { println(\"g\"); }
+            f(); g(); 1 + 1; let a = 3;",
+        );
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let buffer = SharedBuffer::default();
+        let mut evaluator = Evaluator::new();
+        evaluator.set_output(buffer.clone());
+        assert_eq!(
+            evaluator.run(&statements),
+            Outcome {
+                value: Value::Unit,
+                exit_code: 0,
+                diagnostics: vec![],
+            }
+        );
+        assert_eq!(
+            String::from_utf8(buffer.0.borrow().clone()).unwrap(),
+            "f\ng\n"
+        );
+    }
+
+    #[test]
+    fn runtime_error_stops_execution() {
+        let input = String::from("println(\"before\");\n1 / 0;\nprintln(\"after\");");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let buffer = SharedBuffer::default();
+        let mut evaluator = Evaluator::new();
+        evaluator.set_output(buffer.clone());
+        assert_eq!(
+            evaluator.run(&statements),
+            Outcome {
+                value: Value::Unit,
+                exit_code: 1,
+                diagnostics: vec![RuntimeError {
+                    kind: ErrorKind::Arithmetic,
+                    message: "division by zero".to_string(),
+                    position: Position {
+                        index: 19,
+                        line: 2,
+                        column: 1,
+                    },
+                    backtrace: vec![],
+                }],
+            }
+        );
+        assert_eq!(
+            String::from_utf8(buffer.0.borrow().clone()).unwrap(),
+            "before\n"
+        );
+    }
+
+    #[test]
+    fn exit_stops_execution_with_code() {
+        let input = String::from(
+            "let mut i = 0;
+            fn check(i) { if i == 3 { exit(i + 4); } }
+            while true { i += 1; check(i); }
+            i = 100;",
+        );
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let mut evaluator = Evaluator::new();
+        let outcome = evaluator.run(&parser.parse_statements());
+        assert_eq!(outcome.exit_code, 7);
+        assert_eq!(outcome.diagnostics, vec![]);
+
+        let input = String::from("i;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let outcome = evaluator.run(&parser.parse_statements());
+        assert_eq!(outcome.value, Value::Integer(3));
+
+        let input = String::from("exit(2147483648);");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let outcome = evaluator.run(&parser.parse_statements());
+        assert_eq!(
+            outcome.diagnostics,
+            vec![RuntimeError {
+                kind: ErrorKind::Native,
+                message: "exit code 2147483648 is out of range".to_string(),
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+                backtrace: vec![],
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_errors_are_reported_before_execution() {
+        let input = String::from("println(\"not run\");\nlet a = 1\nlet b = \"\\q\";");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let buffer = SharedBuffer::default();
+        let mut evaluator = Evaluator::new();
+        evaluator.set_output(buffer.clone());
+        let outcome = evaluator.run(&statements);
+        assert_eq!(outcome.exit_code, 1);
+        assert_eq!(outcome.diagnostics.len(), 2, "{outcome:#?}");
+        assert!(buffer.0.borrow().is_empty());
+    }
+
+    #[test]
+    fn nested_parse_errors_are_reported_before_execution() {
+        let cases = [
+            "fn f() { let = ; } println(\"ran\"); 1;",
+            "println(\"ran\"); if true { let a = ; }",
+            "println(\"ran\"); let f = fn() { return [1, ]; }; match 1 { 1 => 2, ) => 3 };",
+        ];
+        for input in cases {
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.to_string());
+            let statements = parser.parse_statements();
+            let buffer = SharedBuffer::default();
+            let outcome = Evaluator::evaluate_statements(statements, buffer.clone());
+            assert_eq!(outcome.exit_code, 1, "{input}");
+            assert!(!outcome.diagnostics.is_empty(), "{input}");
+            assert!(
+                outcome
+                    .diagnostics
+                    .iter()
+                    .all(|diagnostic| diagnostic.kind == ErrorKind::Syntax),
+                "{input}: {outcome:#?}"
+            );
+            assert!(buffer.0.borrow().is_empty(), "{input}");
+        }
+    }
+
+    fn list(values: Vec<Value>) -> Value {
+        Value::List(Reference::new(RefCell::new(values)))
+    }
+
+    #[test]
+    fn cyclic_lists() {
+        let input = String::from("let mut xs = [1]; push(xs, xs); println(xs);\nlet mut ys = [1]; push(ys, ys);\nprintln(xs == xs); println(xs == ys); println(xs == [1, [1]]);\nxs;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let buffer = SharedBuffer::default();
+        let outcome = Evaluator::evaluate_statements(statements, buffer.clone());
+        assert_eq!(
+            String::from_utf8(buffer.0.borrow().clone()).unwrap(),
+            "[1, [...]]\ntrue\ntrue\nfalse\n"
+        );
+        assert_eq!(
+            format!("{:?}", outcome.value),
+            "List(RefCell { value: [Integer(1), List([...])] })"
+        );
+    }
+
+    #[test]
+    fn cyclic_maps() {
+        let input = String::from("let mut m = {:}; m[\"s\"] = m; m.n = 1; println(m);\nlet xs = [m]; m.xs = xs; println(xs);\nprintln(m == m); println(m == {\"n\": 1});\nm;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let buffer = SharedBuffer::default();
+        let outcome = Evaluator::evaluate_statements(statements, buffer.clone());
+        assert_eq!(
+            String::from_utf8(buffer.0.borrow().clone()).unwrap(),
+            "{\"n\": 1, \"s\": {...}}\n[{\"n\": 1, \"s\": {...}, \"xs\": [...]}]\ntrue\nfalse\n"
+        );
+        assert_eq!(
+            format!("{:?}", outcome.value),
+            "Map(RefCell { value: {\"n\": Integer(1), \"s\": Map({...}), \"xs\": List(RefCell { value: [Map({...})] })} })"
+        );
+    }
+
+    #[test]
+    fn cyclic_structs() {
+        let input = String::from("struct Node { next, value }\nlet mut a = Node { next: 0, value: 1 };\na.next = a; println(a);\nlet mut b = Node { next: a, value: 1 }; b.next = b;\nprintln(a == a); println(a == b); println(a == Node { next: 0, value: 1 });\na;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let buffer = SharedBuffer::default();
+        let outcome = Evaluator::evaluate_statements(statements, buffer.clone());
+        assert_eq!(
+            String::from_utf8(buffer.0.borrow().clone()).unwrap(),
+            "Node { next: Node {...}, value: 1 }\ntrue\ntrue\nfalse\n"
+        );
+        assert_eq!(
+            format!("{:?}", outcome.value),
+            "Struct(Node { next: Struct({...}), value: Integer(1) })"
+        );
+    }
+
+    #[test]
+    fn lists() {
+        let cases = [
+            (
+                "[1, 2.0, \"3\"];",
+                list(vec![
+                    Value::Integer(1),
+                    Value::Float(2.0),
+                    Value::String("3".to_string()),
+                ]),
+            ),
+            (
+                "let xs = [[1], [2, 3]]; xs[1][0] + xs[0][0];",
+                Value::Integer(3),
+            ),
+            (
+                "let mut xs = [1, 2]; xs[0] = 5; xs[1] += 10; xs;",
+                list(vec![Value::Integer(5), Value::Integer(12)]),
+            ),
+            (
+                "let mut xs = [[1]]; xs[0][0] *= 7; xs[0][0];",
+                Value::Integer(7),
+            ),
+            (
+                "let mut xs = []; push(xs, 1); push(xs, 2); len(xs) * 10 + pop(xs);",
+                Value::Integer(22),
+            ),
+            (
+                "let mut xs = [1]; let mut ys = xs; push(ys, 2); xs == [1, 2];",
+                Value::Bool(true),
+            ),
+            (
+                "let mut xs = [1]; fn set(mut ys) { ys[0] = 2; } set(xs); xs[0];",
+                Value::Integer(2),
+            ),
+            ("len(\"hé\");", Value::Integer(2)),
+        ];
+        for (input, expected) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(outcome.diagnostics, vec![], "{input}");
+            assert_eq!(expected, outcome.value, "{input}");
+        }
+        assert_eq!(
+            list(vec![
+                Value::Integer(1),
+                Value::String("a".to_string()),
+                list(vec![])
+            ])
+            .to_printable(),
+            "[1, \"a\", []]"
+        );
+    }
+
+    #[test]
+    fn list_errors() {
+        let cases = [
+            (
+                "let xs = [1, 2];\nxs[2];",
+                ErrorKind::Index,
+                "index 2 out of bounds for list of length 2",
+                20,
+                2,
+                4,
+            ),
+            (
+                "let xs = [1, 2];\nxs[-1];",
+                ErrorKind::Index,
+                "index -1 out of bounds for list of length 2",
+                20,
+                2,
+                4,
+            ),
+            (
+                "let xs = [1, 2];\nxs[0.0];",
+                ErrorKind::Type,
+                "list index must be integer, got float",
+                20,
+                2,
+                4,
+            ),
+            (
+                "let x = 1;\nx[0];",
+                ErrorKind::Type,
+                "cannot index into integer",
+                11,
+                2,
+                1,
+            ),
+            (
+                "let xs = [1, 2];\nxs[0] = 3;",
+                ErrorKind::Immutable,
+                "identifier xs is not mutable",
+                17,
+                2,
+                1,
+            ),
+            (
+                "let mut xs = [1];\nxs[1] = 3;",
+                ErrorKind::Index,
+                "index 1 out of bounds for list of length 1",
+                21,
+                2,
+                4,
+            ),
+            (
+                "let mut xs = [\"a\"];\nxs[0] -= 1;",
+                ErrorKind::Type,
+                "no implementation exists for string -= integer",
+                20,
+                2,
+                1,
+            ),
+            (
+                "let mut xs = [];\npop(xs);",
+                ErrorKind::Native,
+                "cannot pop from empty list",
+                17,
+                2,
+                1,
+            ),
+        ];
+        for (input, kind, message, index, line, column) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(
+                outcome.diagnostics,
+                vec![RuntimeError {
+                    kind,
+                    message: message.to_string(),
+                    position: Position {
+                        index,
+                        line,
+                        column,
+                    },
+                    backtrace: vec![],
+                }],
+                "{input}"
+            );
         }
     }
-    fn evaluate_binary_expression(
-        node: Node<Expression>,
-        outer_context: &mut HashMap<String, IdentifierType>,
-        inner_context: &mut HashMap<String, IdentifierType>,
-    ) -> Value {
-        let Expression::Binary { left, right, variant } = node.value else {
-            panic!("expected Binary, got {:#?}", node.value);
-        };
 
-        macro_rules! impl_variant {
-            ($func_name:ident) => {
-                Self::evaluate_expression(*left, outer_context, inner_context)
-                    .$func_name(Self::evaluate_expression(
-                        *right,
-                        outer_context,
-                        inner_context,
-                    ))
-                    .unwrap_or_else(|message| Value::Error {
-                        message,
-                        line: node.position.line,
-                        column: node.position.column,
-                    })
-            };
+    #[test]
+    fn immutable_lists_cannot_be_mutated_through_aliases() {
+        let cases = [
+            (
+                "let xs = [1, 2];\nlet mut ys = xs;\nys[0] = 99;",
+                ErrorKind::Immutable,
+            ),
+            ("let zs = [1];\npush(zs, 5);", ErrorKind::Native),
+            ("let zs = [1];\npop(zs);", ErrorKind::Native),
+            (
+                "fn set(mut xs) { xs[0] = 99; }\nlet xs = [1, 2];\nset(xs);",
+                ErrorKind::Immutable,
+            ),
+            (
+                "let xs = [[1]];\nlet mut ys = xs;\nys[0][0] = 99;",
+                ErrorKind::Immutable,
+            ),
+            (
+                "let inner = [1];\nlet mut outer = [inner];\npush(outer[0], 2);",
+                ErrorKind::Native,
+            ),
+        ];
+        for (input, kind) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            let diagnostics: Vec<_> = outcome
+                .diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.kind, diagnostic.message.as_str()))
+                .collect();
+            assert_eq!(
+                diagnostics,
+                vec![(kind, "cannot mutate immutable list")],
+                "{input}"
+            );
         }
+    }
 
-        match variant {
-            BinaryVariant::Addition => impl_variant!(add),
-            BinaryVariant::Subtraction => impl_variant!(div),
-            BinaryVariant::Multiplication => impl_variant!(mul),
-            BinaryVariant::Division => impl_variant!(sub),
-            BinaryVariant::Equal => Value::Bool(
-                Self::evaluate_expression(*left, outer_context, inner_context)
-                    == Self::evaluate_expression(*right, outer_context, inner_context),
+    #[test]
+    fn maps() {
+        let cases = [
+            (
+                "let m = { \"b\": 2, \"a\": 1 }; m[\"a\"] * 10 + m.b;",
+                Value::Integer(12),
             ),
-            BinaryVariant::NotEqual => Value::Bool(
-                Self::evaluate_expression(*left, outer_context, inner_context)
-                    != Self::evaluate_expression(*right, outer_context, inner_context),
+            (
+                "let mut m = {:}; m[\"x\"] = 1; m.x += 2; m.y = [m.x]; m.y[0];",
+                Value::Integer(3),
             ),
+            (
+                "let m = { \"z\": 1, \"a\": 2, \"m\": 3 }; keys(m);",
+                list(vec![
+                    Value::String("a".to_string()),
+                    Value::String("m".to_string()),
+                    Value::String("z".to_string()),
+                ]),
+            ),
+            (
+                "let m = { \"a\": 1 }; has(m, \"a\") && !has(m, \"b\");",
+                Value::Bool(true),
+            ),
+            (
+                "let mut m = { \"a\": 1, \"b\": 2 }; remove(m, \"a\") + len(m);",
+                Value::Integer(2),
+            ),
+            (
+                "{ \"a\": [1], \"b\": {:} } == { \"b\": {:}, \"a\": [1] };",
+                Value::Bool(true),
+            ),
+            (
+                "let mut m = { \"a\": { \"b\": 1 } }; let mut n = m; n.a.b = 5; m.a.b;",
+                Value::Integer(5),
+            ),
+        ];
+        for (input, expected) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(outcome.diagnostics, vec![], "{input}");
+            assert_eq!(expected, outcome.value, "{input}");
         }
+
+        let input = String::from("{ \"b\": \"2\", \"a\": [1] };");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(outcome.value.to_printable(), "{\"a\": [1], \"b\": \"2\"}");
     }
-    pub fn evaluate_expression(
-        expression: Node<Expression>,
-        outer_context: &mut HashMap<String, IdentifierType>,
-        inner_context: &mut HashMap<String, IdentifierType>,
-    ) -> Value {
-        match expression.value {
-            Expression::Integer(v) => Value::Integer(v),
-            Expression::Float(v) => Value::Float(v),
-            Expression::Unary { subject, variant } => {
-                let evaluated_subject =
-                    Self::evaluate_expression(*subject, outer_context, inner_context);
-                match (variant, evaluated_subject) {
-                    (UnaryVariant::NegateNumber, Value::Integer(v)) => Value::Integer(-v),
-                    (UnaryVariant::NegateNumber, Value::Float(v)) => Value::Float(-v),
-                    (UnaryVariant::NegateBool, Value::Bool(v)) => Value::Bool(!v),
-                    (UnaryVariant::NegateBool, v @ (Value::Integer(_) | Value::Float(_))) => {
-                        let message = format!("expected bool, got {v:#?}");
-                        let Position { line, column, .. } = expression.position;
-                        Value::Error {
-                            message,
-                            line,
-                            column,
-                        }
-                    }
-                    (UnaryVariant::NegateNumber, v @ Value::Bool(_)) => {
-                        let message = format!("expected number, got {v:#?}");
-                        let Position { line, column, .. } = expression.position;
-                        Value::Error {
-                            message,
-                            line,
-                            column,
-                        }
-                    }
-                    (
-                        _,
-                        Value::Error {
-                            message,
-                            line,
-                            column,
-                        },
-                    ) => Value::Error {
-                        message,
+
+    #[test]
+    fn map_errors() {
+        let cases = [
+            (
+                "let m = {:};\nm[\"a\"];",
+                ErrorKind::Index,
+                "key \"a\" not found in map",
+                15,
+                2,
+                3,
+            ),
+            (
+                "let m = {:};\nm.a;",
+                ErrorKind::Index,
+                "key \"a\" not found in map",
+                15,
+                2,
+                3,
+            ),
+            (
+                "let mut m = {:};\nm.a += 1;",
+                ErrorKind::Index,
+                "key \"a\" not found in map",
+                19,
+                2,
+                3,
+            ),
+            (
+                "let m = {:};\nm.a = 1;",
+                ErrorKind::Immutable,
+                "identifier m is not mutable",
+                13,
+                2,
+                1,
+            ),
+            (
+                "let m = {:};\nm[1];",
+                ErrorKind::Type,
+                "map key must be string, got integer",
+                15,
+                2,
+                3,
+            ),
+            (
+                "let x = [1];\nx.a;",
+                ErrorKind::Type,
+                "cannot access member a on list",
+                15,
+                2,
+                3,
+            ),
+            (
+                "let mut m = {:};\nremove(m, \"a\");",
+                ErrorKind::Native,
+                "key \"a\" not found in map",
+                17,
+                2,
+                1,
+            ),
+        ];
+        for (input, kind, message, index, line, column) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(
+                outcome.diagnostics,
+                vec![RuntimeError {
+                    kind,
+                    message: message.to_string(),
+                    position: Position {
+                        index,
                         line,
                         column,
                     },
-                }
-            }
-            Expression::Binary { .. } => {
-                Self::evaluate_binary_expression(expression, outer_context, inner_context)
-            }
-            Expression::Call {
-                subject: _,
-                arguments: _,
-            } => todo!("evaluate call expressions"),
-            Expression::Identifier(q) => match inner_context.get(&q).or(outer_context.get(&q)) {
-                Some(IdentifierType::Value { value, .. }) => value.clone(),
-                Some(IdentifierType::Function { value: _ }) => todo!("function identifiers"),
-                None => Value::Error {
-                    message: format!("identifier {q} is not yet given value"),
-                    line: expression.position.line,
-                    column: expression.position.column,
-                },
-            },
-            Expression::Error(message) => {
-                let Position { line, column, .. } = expression.position;
-                Value::Error {
-                    message,
-                    line,
-                    column,
-                }
-            }
+                    backtrace: vec![],
+                }],
+                "{input}"
+            );
         }
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use couch_lang_lexer::Lexer;
-    use couch_lang_parser::Parser;
-    use pretty_assertions::assert_eq;
+    #[test]
+    fn immutable_maps_cannot_be_mutated_through_aliases() {
+        let cases = [
+            (
+                "let m = { \"k\": 0 };\nremove(m, \"k\");",
+                ErrorKind::Native,
+            ),
+            (
+                "let m = { \"k\": 0 };\nlet mut n = m;\nn[\"k\"] = 1;",
+                ErrorKind::Immutable,
+            ),
+            (
+                "let m = { \"k\": 0 };\nlet mut n = m;\nn.k += 1;",
+                ErrorKind::Immutable,
+            ),
+            (
+                "fn set(mut n) { n.k = 1; }\nlet m = {:};\nset(m);",
+                ErrorKind::Immutable,
+            ),
+            (
+                "let m = { \"inner\": { \"k\": 0 } };\nlet mut n = m;\nn.inner.k = 1;",
+                ErrorKind::Immutable,
+            ),
+        ];
+        for (input, kind) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            let diagnostics: Vec<_> = outcome
+                .diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.kind, diagnostic.message.as_str()))
+                .collect();
+            assert_eq!(
+                diagnostics,
+                vec![(kind, "cannot mutate immutable map")],
+                "{input}"
+            );
+        }
+    }
 
-    use super::*;
+    #[test]
+    fn immutable_structs_cannot_be_mutated_through_aliases() {
+        let point = "struct Point { x, y }\nimpl Point { fn move_by(mut self, dx) { self.x += dx; } }\nlet p = Point { x: 1, y: Point { x: 2, y: 3 } };\n";
+        let cases = [
+            "let mut q = p;\nq.x = 5;",
+            "let mut q = p;\nq.move_by(1);",
+            "fn shift(mut q) { q.x += 1; }\nshift(p);",
+            "fn shift(mut q) { q.move_by(1); }\nshift(p);",
+            "let mut q = p;\nq.y.x = 5;",
+            "let mut q = p.y;\nq.move_by(1);",
+        ];
+        for input in cases {
+            let input = format!("{point}{input}");
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            let diagnostics: Vec<_> = outcome
+                .diagnostics
+                .iter()
+                .map(|diagnostic| (diagnostic.kind, diagnostic.message.as_str()))
+                .collect();
+            assert_eq!(
+                diagnostics,
+                vec![(ErrorKind::Immutable, "cannot mutate immutable Point")],
+                "{input}"
+            );
+        }
+    }
 
     #[test]
-    fn add_expression() {
-        let input = String::from("2 + 4");
+    fn structs() {
+        let point = "struct Point { x, y }\nimpl Point {\n    fn len(self) { return self.x * self.x + self.y * self.y; }\n    fn scaled(self, by) { return Point { x: self.x * by, y: self.y * by }; }\n    fn move_by(mut self, dx) { self.x += dx; }\n}\n";
+        let cases = [
+            (
+                "let p = Point { x: 1, y: 2 }; p.x + p.y;",
+                Value::Integer(3),
+            ),
+            (
+                "let mut p = Point { y: 2, x: 1 }; p.x = 10; p.y += 1; p.x * p.y;",
+                Value::Integer(30),
+            ),
+            ("let p = Point { x: 3, y: 4 }; p.len();", Value::Integer(25)),
+            (
+                "let p = Point { x: 1, y: 2 }; p.scaled(3).len();",
+                Value::Integer(45),
+            ),
+            (
+                "let mut p = Point { x: 1, y: 2 }; p.move_by(4); p.move_by(1); p.x;",
+                Value::Integer(6),
+            ),
+            (
+                "let mut a = Point { x: 1, y: 2 }; let mut b = a; b.x = 5; a.x;",
+                Value::Integer(5),
+            ),
+            (
+                "let mut a = Point { x: 1, y: 2 }; let mut b = a; b.move_by(2); a.x;",
+                Value::Integer(3),
+            ),
+            (
+                "Point { x: 1, y: [2] } == Point { x: 1, y: [2] };",
+                Value::Bool(true),
+            ),
+            (
+                "type_of(Point { x: 1, y: 2 });",
+                Value::String("Point".to_string()),
+            ),
+        ];
+        for (input, expected) in cases {
+            let input = format!("{point}{input}");
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(outcome.diagnostics, vec![], "{input}");
+            assert_eq!(expected, outcome.value, "{input}");
+        }
+
+        let input = String::from(
+            "struct Empty {}\nstruct Pair { a, b }\n[Pair { a: \"x\", b: Empty {} }];",
+        );
         let lexer = Lexer::new(input.chars());
         let mut parser = Parser::new(lexer.into_iter(), input.clone());
-        let expression = parser.parse_expression();
-        let mut id_table_0 = HashMap::new();
-        let mut id_table_1 = HashMap::new();
-        let value = Evaluator::evaluate_expression(expression, &mut id_table_0, &mut id_table_1);
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
         assert_eq!(
-            6,
-            match value {
-                Value::Integer(v) => v,
-                value => panic!("expected Integer, got {value:#?}"),
-            }
-        )
+            outcome.value.to_printable(),
+            "[Pair { a: \"x\", b: Empty {} }]"
+        );
     }
 
     #[test]
-    fn identifier() {
-        let input = String::from("a");
-        let lexer = Lexer::new(input.chars());
+    fn struct_errors() {
+        let cases = [
+            (
+                "struct Point { x, y }\nlet p = Point { x: 1, y: 2 };\np.z;",
+                ErrorKind::Field,
+                "no field `z` on Point",
+                54,
+                3,
+                3,
+            ),
+            (
+                "struct Point { x, y }\nlet mut p = Point { x: 1, y: 2 };\np.z = 1;",
+                ErrorKind::Field,
+                "no field `z` on Point",
+                58,
+                3,
+                3,
+            ),
+            (
+                "struct Point { x, y }\nPoint { x: 1, z: 2 };",
+                ErrorKind::Field,
+                "no field `z` on Point",
+                36,
+                2,
+                15,
+            ),
+            (
+                "struct Point { x, y }\nPoint { x: 1, x: 2 };",
+                ErrorKind::Field,
+                "field `x` specified more than once",
+                36,
+                2,
+                15,
+            ),
+            (
+                "struct Point { x, y }\nPoint { x: 1 };",
+                ErrorKind::Field,
+                "missing field `y` in Point literal",
+                22,
+                2,
+                1,
+            ),
+            (
+                "struct Point { x, x }",
+                ErrorKind::Field,
+                "field `x` declared more than once on Point",
+                18,
+                1,
+                19,
+            ),
+            (
+                "struct Point { x, y }\nlet p = Point { x: 1, y: 2 };\np.x = 3;",
+                ErrorKind::Immutable,
+                "identifier p is not mutable",
+                52,
+                3,
+                1,
+            ),
+            (
+                "struct C { n }\nimpl C { fn bump(mut self) { self.n += 1; } }\nlet c = C { n: 1 };\nc.bump();",
+                ErrorKind::Immutable,
+                "identifier c is not mutable",
+                81,
+                4,
+                1,
+            ),
+            (
+                "struct C { n }\nimpl C { fn get(self) { return self.n; } }\nlet c = C { n: 1 };\nc.get(2);",
+                ErrorKind::Arity,
+                "function C.get takes 0 arguments, got 1",
+                78,
+                4,
+                1,
+            ),
+            (
+                "struct C { n }\nimpl C { fn get() { return 1; } }",
+                ErrorKind::Type,
+                "method get on C must take self as its first parameter",
+                24,
+                2,
+                10,
+            ),
+            (
+                "impl C { fn get(self) { return 1; } }",
+                ErrorKind::UndefinedName,
+                "identifier C not defined",
+                5,
+                1,
+                6,
+            ),
+            (
+                "struct C { n }\nC;",
+                ErrorKind::Type,
+                "struct C is not a value",
+                15,
+                2,
+                1,
+            ),
+            (
+                "struct C { n }\nC();",
+                ErrorKind::Type,
+                "identifier C is a struct, not a function",
+                15,
+                2,
+                1,
+            ),
+        ];
+        for (input, kind, message, index, line, column) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(
+                outcome.diagnostics,
+                vec![RuntimeError {
+                    kind,
+                    message: message.to_string(),
+                    position: Position {
+                        index,
+                        line,
+                        column,
+                    },
+                    backtrace: vec![],
+                }],
+                "{input}"
+            );
+        }
+    }
 
-        let mut parser = Parser::new(lexer.into_iter(), input.clone());
-        let expression = parser.parse_expression();
+    #[test]
+    fn enums_and_match() {
+        let shape = "enum Shape { Circle(radius), Rect(width, height), Empty }\nfn area(shape) {\n    return match shape {\n        Shape::Circle(r) => 3 * r * r,\n        Shape::Rect(w, h) if w == h => w * w,\n        Shape::Rect(w, h) => w * h,\n        Shape::Empty => 0,\n    };\n}\n";
+        let cases = [
+            ("area(Shape::Circle(2));", Value::Integer(12)),
+            ("area(Shape::Rect(3, 3)) + area(Shape::Rect(2, 5));", Value::Integer(19)),
+            ("area(Shape::Empty);", Value::Integer(0)),
+            ("Shape::Rect(1, [2]) == Shape::Rect(1, [2]);", Value::Bool(true)),
+            ("Shape::Empty != Shape::Circle(0);", Value::Bool(true)),
+            (
+                "type_of(Shape::Empty);",
+                Value::String("Shape".to_string()),
+            ),
+            (
+                "let make = Shape::Circle; area(make(1));",
+                Value::Integer(3),
+            ),
+            (
+                "match 5 { 1 => \"one\", -5 => \"minus\", n if n > 3 => \"big\", _ => \"small\" };",
+                Value::String("big".to_string()),
+            ),
+            (
+                "match \"b\" { \"a\" => 1, \"b\" => { let x = 2; x * 10; } _ => 3 };",
+                Value::Integer(20),
+            ),
+            (
+                "match true { true => 1, false => 0 };",
+                Value::Integer(1),
+            ),
+            (
+                "enum Option { Some(value), None }\nmatch Option::Some(Shape::Circle(4)) {\n    Option::Some(Shape::Circle(r)) => r,\n    Option::Some(_) => 1,\n    Option::None => 0,\n};",
+                Value::Integer(4),
+            ),
+        ];
+        for (input, expected) in cases {
+            let input = format!("{shape}{input}");
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(outcome.diagnostics, vec![], "{input}");
+            assert_eq!(expected, outcome.value, "{input}");
+        }
 
-        let mut id_table_0 = HashMap::from([(
-            "a".to_string(),
-            IdentifierType::Value {
-                value: Value::Integer(5),
-                mutable: false,
-            },
-        )]);
-        let mut id_table_1 = HashMap::new();
-        let value = Evaluator::evaluate_expression(expression, &mut id_table_0, &mut id_table_1);
+        let input = format!("{shape}[Shape::Rect(1.5, \"x\"), Shape::Empty];");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let outcome = Evaluator::evaluate_statements(statements, sink());
         assert_eq!(
-            5,
-            match value {
-                Value::Integer(v) => v,
-                value => panic!("expected Integer, got {value:#?}"),
-            }
-        )
+            outcome.value.to_printable(),
+            "[Shape::Rect(1.5, \"x\"), Shape::Empty]"
+        );
     }
 
     #[test]
-    fn equals_expression() {
-        let input = String::from("2 == 4");
-        let lexer = Lexer::new(input.chars());
+    fn match_errors() {
+        let cases = [
+            (
+                "enum E { A, B(x) }\nmatch E::A {\n    E::A => 1,\n}\n;",
+                ErrorKind::Match,
+                "missing match arms for E::B(_)",
+                19,
+                2,
+                1,
+            ),
+            (
+                "enum E { A, B(x) }\nenum O { S(v), N }\nfn f(o) {\n    return match o { O::S(E::A) => 1, O::N => 2 };\n}",
+                ErrorKind::Match,
+                "missing match arms for O::S(E::B(_))",
+                59,
+                4,
+                12,
+            ),
+            (
+                "enum E { A, B, C }\nmatch E::A { E::A => 1, E::B if true => 2 };",
+                ErrorKind::Match,
+                "missing match arms for E::B, E::C",
+                19,
+                2,
+                1,
+            ),
+            (
+                "match true { true => 1 };",
+                ErrorKind::Match,
+                "missing match arms for false",
+                0,
+                1,
+                1,
+            ),
+            (
+                "match 1 { 1 => 1, 2 => 2 };",
+                ErrorKind::Match,
+                "missing match arms for _",
+                0,
+                1,
+                1,
+            ),
+            (
+                "enum E { A, B(x) }\nE::C;",
+                ErrorKind::Field,
+                "no variant `C` on E",
+                22,
+                2,
+                4,
+            ),
+            (
+                "enum E { A, B(x) }\nmatch E::A { E::B(x, y) => 1, _ => 2 };",
+                ErrorKind::Arity,
+                "variant E::B has 1 fields, pattern has 2",
+                32,
+                2,
+                14,
+            ),
+            (
+                "enum E { A, B(x) }\nmatch E::A { n if n => 1, _ => 2 };",
+                ErrorKind::Type,
+                "expected bool, got E",
+                37,
+                2,
+                19,
+            ),
+            (
+                "enum E { A, A }",
+                ErrorKind::Field,
+                "variant `A` declared more than once on E",
+                12,
+                1,
+                13,
+            ),
+            (
+                "enum E { A }\nE;",
+                ErrorKind::Type,
+                "enum E is not a value",
+                13,
+                2,
+                1,
+            ),
+        ];
+        for (input, kind, message, index, line, column) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = Evaluator::evaluate_statements(statements, sink());
+            assert_eq!(
+                outcome.diagnostics,
+                vec![RuntimeError {
+                    kind,
+                    message: message.to_string(),
+                    position: Position {
+                        index,
+                        line,
+                        column,
+                    },
+                    backtrace: vec![],
+                }],
+                "{input}"
+            );
+        }
+    }
 
+    #[test]
+    fn non_exhaustive_match_runs_nothing() {
+        let input = String::from("println(\"hi\");\nenum E { A, B }\nfn f(e) { return match e { E::A => 1 }; }\nmatch 1 { 1 => 1 };");
+        let lexer = Lexer::new(input.chars());
         let mut parser = Parser::new(lexer.into_iter(), input.clone());
-        let expression = parser.parse_expression();
+        let statements = parser.parse_statements();
+        let buffer = SharedBuffer::default();
+        let mut evaluator = Evaluator::new();
+        evaluator.set_output(buffer.clone());
+        let outcome = evaluator.run(&statements);
+        assert_eq!(
+            outcome
+                .diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "match error at 3:18: missing match arms for E::B".to_string(),
+                "match error at 4:1: missing match arms for _".to_string(),
+            ]
+        );
+        assert_eq!(outcome.exit_code, 1);
+        assert!(buffer.0.borrow().is_empty());
+    }
+
+    #[test]
+    fn exhaustiveness_sees_enums_from_earlier_runs() {
+        let mut evaluator = Evaluator::new();
+        evaluator.set_output(sink());
+        for (input, expected) in [
+            ("enum E { A, B(x) }", vec![]),
+            (
+                "match E::A { E::A => 1 };",
+                vec!["match error at 1:1: missing match arms for E::B(_)".to_string()],
+            ),
+        ] {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let outcome = evaluator.run(&statements);
+            assert_eq!(
+                outcome
+                    .diagnostics
+                    .iter()
+                    .map(|diagnostic| diagnostic.to_string())
+                    .collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
 
-        let mut id_table_0 = HashMap::new();
-        let mut id_table_1 = HashMap::new();
-        let value = Evaluator::evaluate_expression(expression, &mut id_table_0, &mut id_table_1);
+    #[test]
+    fn resolution_errors_run_nothing() {
+        let input = String::from("println(\"hi\");\nlet x = 1;\nx = 2;\ny + 1;\nfn f(a) { a += 1; return g(a); }\nreturn x;\nfn g(b) { return undefined_call(b); }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let buffer = SharedBuffer::default();
+        let mut evaluator = Evaluator::new();
+        evaluator.set_output(buffer.clone());
+        let outcome = evaluator.run(&statements);
         assert_eq!(
-            false,
-            match value {
-                Value::Bool(v) => v,
-                value => panic!("expected Bool, got {value:#?}"),
-            }
-        )
+            outcome
+                .diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect::<Vec<_>>(),
+            vec![
+                "immutable binding at 3:1: identifier x is not mutable".to_string(),
+                "undefined name at 4:1: identifier y is not yet given value".to_string(),
+                "immutable binding at 5:11: identifier a is not mutable".to_string(),
+                "control flow error at 6:1: return outside of function".to_string(),
+                "undefined name at 7:18: identifier undefined_call not defined".to_string(),
+            ]
+        );
+        assert_eq!(outcome.exit_code, 1);
+        assert!(buffer.0.borrow().is_empty());
     }
 
     #[test]
-    fn not_equals_expression() {
-        let input = String::from("2 != 4");
+    fn resolver_allows_late_bound_names() {
+        let input = String::from("fn is_even(n) { if n == 0 { return true; } return is_odd(n - 1); }\nfn is_odd(n) { if n == 0 { return false; } return is_even(n - 1); }\nlet c = 1;\nlet mut c = c + 1;\nc += 1;\nlet add = fn(a) { return a + c + offset; };\nlet offset = 10;\nis_even(4) && add(1) == 14;");
         let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let mut evaluator = Evaluator::new();
+        assert_eq!(Value::Bool(true), evaluator.run(&statements).value);
 
+        let input = String::from("c = c + offset;");
+        let lexer = Lexer::new(input.chars());
         let mut parser = Parser::new(lexer.into_iter(), input.clone());
-        let expression = parser.parse_expression();
+        let statements = parser.parse_statements();
+        assert_eq!(
+            Vec::<RuntimeError>::new(),
+            evaluator.run(&statements).diagnostics
+        );
 
-        let mut id_table_0 = HashMap::new();
-        let mut id_table_1 = HashMap::new();
-        let value = Evaluator::evaluate_expression(expression, &mut id_table_0, &mut id_table_1);
+        let input = String::from("offset = 1;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
         assert_eq!(
-            true,
-            match value {
-                Value::Bool(v) => v,
-                value => panic!("expected Bool, got {value:#?}"),
-            }
-        )
+            vec!["immutable binding at 1:1: identifier offset is not mutable".to_string()],
+            evaluator
+                .run(&statements)
+                .diagnostics
+                .iter()
+                .map(|diagnostic| diagnostic.to_string())
+                .collect::<Vec<_>>()
+        );
     }
 
     #[test]
-    fn add_float_and_int_should_fail() {
-        let input = String::from("2.5 + 4");
+    fn resolver_assigns_slots() {
+        use resolver::Binding;
+
+        let input =
+            String::from("let a = 1;\nfn f(b) { let c = b; return fn() { return a + c; }; }");
         let lexer = Lexer::new(input.chars());
         let mut parser = Parser::new(lexer.into_iter(), input.clone());
-        let expression = parser.parse_expression();
-        let mut id_table_0 = HashMap::new();
-        let mut id_table_1 = HashMap::new();
-        let error = Evaluator::evaluate_expression(expression, &mut id_table_0, &mut id_table_1);
-        assert_eq!(
-            Value::Error {
-                message: "no implementation exists for float + integer".to_string(),
-                column: 1,
-                line: 1
-            },
-            error
-        );
+        let statements = parser.parse_statements();
+        let resolution = resolver::resolve(&statements, &Environment::new()).unwrap();
+        let binding = |index| {
+            resolution.binding(&Position {
+                index,
+                line: 0,
+                column: 0,
+            })
+        };
+        assert_eq!(Some(Binding::Global), binding(4));
+        assert_eq!(Some(Binding::Local { depth: 0, slot: 0 }), binding(16));
+        assert_eq!(Some(Binding::Local { depth: 0, slot: 1 }), binding(25));
+        assert_eq!(Some(Binding::Local { depth: 0, slot: 0 }), binding(29));
+        assert_eq!(Some(Binding::Global), binding(53));
+        assert_eq!(Some(Binding::Local { depth: 1, slot: 1 }), binding(57));
     }
 
     #[test]
-    fn evaluate_let_chain() {
-        let input = String::from("let mut a = 5; a += 5; a;");
+    fn shadowed_captures_resolve_to_their_own_slots() {
+        use resolver::Binding;
+
+        let input = String::from("let x = 1;\nfn f(x) {\n    let g = fn() { return x; };\n    let mut h = g;\n    {\n        let x = 100;\n        h = fn() { return x; };\n    }\n    return g() * 1000 + h() + x;\n}\nf(7) + x;");
         let lexer = Lexer::new(input.chars());
         let mut parser = Parser::new(lexer.into_iter(), input.clone());
         let statements = parser.parse_statements();
-        let error = Evaluator::evaluate_statements(statements);
-        assert_eq!(Some(Value::Integer(10)), error);
+        let resolution = resolver::resolve(&statements, &Environment::new()).unwrap();
+        let bindings: Vec<_> = input
+            .match_indices('x')
+            .map(|(index, _)| {
+                resolution.binding(&Position {
+                    index,
+                    line: 0,
+                    column: 0,
+                })
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                Some(Binding::Global),
+                Some(Binding::Local { depth: 0, slot: 0 }),
+                Some(Binding::Local { depth: 1, slot: 0 }),
+                Some(Binding::Local { depth: 0, slot: 0 }),
+                Some(Binding::Local { depth: 1, slot: 0 }),
+                Some(Binding::Local { depth: 0, slot: 0 }),
+                Some(Binding::Global),
+            ],
+            bindings
+        );
+
+        let outcome = Evaluator::evaluate_statements(statements, sink());
+        assert_eq!(outcome.diagnostics, vec![]);
+        assert_eq!(outcome.value, Value::Integer(7108));
     }
 }
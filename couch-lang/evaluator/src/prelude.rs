@@ -0,0 +1,149 @@
+use std::{
+    cell::{Cell, RefCell},
+    io::Write,
+    rc::Rc,
+};
+
+use crate::{
+    value::{Reference, Value},
+    Evaluator,
+};
+
+pub type Output = Rc<RefCell<Box<dyn Write>>>;
+
+fn write(output: &Output, text: &str) -> Result<Value, String> {
+    output
+        .borrow_mut()
+        .write_all(text.as_bytes())
+        .map_err(|error| format!("failed to write output: {error}"))?;
+    Ok(Value::Unit)
+}
+
+pub(crate) fn register_prelude(
+    evaluator: &mut Evaluator,
+    output: &Output,
+    exit_request: &Rc<Cell<Option<i32>>>,
+) {
+    evaluator.register("int", 1, |arguments| arguments[0].clone().to_integer());
+    evaluator.register("float", 1, |arguments| arguments[0].clone().to_float());
+
+    let print_output = Rc::clone(output);
+    evaluator.register("print", 1, move |arguments| {
+        write(&print_output, &arguments[0].to_printable())
+    });
+    let println_output = Rc::clone(output);
+    evaluator.register("println", 1, move |arguments| {
+        write(
+            &println_output,
+            &format!("{}\n", arguments[0].to_printable()),
+        )
+    });
+
+    let exit_request = Rc::clone(exit_request);
+    evaluator.register("exit", 1, move |arguments| match &arguments[0] {
+        Value::Integer(code) => {
+            let code =
+                i32::try_from(*code).map_err(|_| format!("exit code {code} is out of range"))?;
+            exit_request.set(Some(code));
+            Ok(Value::Unit)
+        }
+        value => Err(format!("expected integer, got {value}")),
+    });
+
+    evaluator.register("type_of", 1, |arguments| {
+        Ok(Value::String(arguments[0].to_string()))
+    });
+    evaluator.register("assert", 1, |arguments| match &arguments[0] {
+        Value::Bool(true) => Ok(Value::Unit),
+        Value::Bool(false) => Err("assertion failed".to_string()),
+        value => Err(format!("expected bool, got {value}")),
+    });
+    evaluator.register("assert_eq", 2, |arguments| match arguments {
+        [left, right] if left == right => Ok(Value::Unit),
+        [left, right] => Err(format!(
+            "assertion failed: {} != {}",
+            left.to_printable(),
+            right.to_printable()
+        )),
+        _ => unreachable!("arity is checked by the evaluator"),
+    });
+
+    evaluator.register("len", 1, |arguments| match &arguments[0] {
+        Value::List(list) => Ok(Value::Integer(list.borrow().len() as i64)),
+        Value::Map(map) => Ok(Value::Integer(map.borrow().len() as i64)),
+        Value::String(value) => Ok(Value::Integer(value.chars().count() as i64)),
+        value => Err(format!("expected list, map or string, got {value}")),
+    });
+    evaluator.register("push", 2, |arguments| match arguments {
+        [Value::List(list), _] if !list.mutable() => {
+            Err("cannot mutate immutable list".to_string())
+        }
+        [Value::List(list), value] => {
+            list.borrow_mut().push(value.clone());
+            Ok(Value::Unit)
+        }
+        [value, _] => Err(format!("expected list, got {value}")),
+        _ => unreachable!("arity is checked by the evaluator"),
+    });
+    evaluator.register("pop", 1, |arguments| match &arguments[0] {
+        Value::List(list) if !list.mutable() => Err("cannot mutate immutable list".to_string()),
+        Value::List(list) => list
+            .borrow_mut()
+            .pop()
+            .ok_or_else(|| "cannot pop from empty list".to_string()),
+        value => Err(format!("expected list, got {value}")),
+    });
+
+    evaluator.register("keys", 1, |arguments| match &arguments[0] {
+        Value::Map(map) => {
+            let keys = map.borrow().keys().cloned().map(Value::String).collect();
+            Ok(Value::List(Reference::new(RefCell::new(keys))))
+        }
+        value => Err(format!("expected map, got {value}")),
+    });
+    evaluator.register("has", 2, |arguments| match arguments {
+        [Value::Map(map), Value::String(key)] => Ok(Value::Bool(map.borrow().contains_key(key))),
+        [Value::Map(_), key] => Err(format!("map key must be string, got {key}")),
+        [value, _] => Err(format!("expected map, got {value}")),
+        _ => unreachable!("arity is checked by the evaluator"),
+    });
+    evaluator.register("remove", 2, |arguments| match arguments {
+        [Value::Map(map), _] if !map.mutable() => Err("cannot mutate immutable map".to_string()),
+        [Value::Map(map), Value::String(key)] => map
+            .borrow_mut()
+            .remove(key)
+            .ok_or_else(|| format!("key {key:?} not found in map")),
+        [Value::Map(_), key] => Err(format!("map key must be string, got {key}")),
+        [value, _] => Err(format!("expected map, got {value}")),
+        _ => unreachable!("arity is checked by the evaluator"),
+    });
+
+    evaluator.register("min", 2, |arguments| match arguments {
+        [Value::Integer(a), Value::Integer(b)] => Ok(Value::Integer(*a.min(b))),
+        [Value::Float(a), Value::Float(b)] => Ok(Value::Float(a.min(*b))),
+        [a, b] => Err(format!("no implementation exists for min({a}, {b})")),
+        _ => unreachable!("arity is checked by the evaluator"),
+    });
+    evaluator.register("max", 2, |arguments| match arguments {
+        [Value::Integer(a), Value::Integer(b)] => Ok(Value::Integer(*a.max(b))),
+        [Value::Float(a), Value::Float(b)] => Ok(Value::Float(a.max(*b))),
+        [a, b] => Err(format!("no implementation exists for max({a}, {b})")),
+        _ => unreachable!("arity is checked by the evaluator"),
+    });
+    evaluator.register("abs", 1, |arguments| match &arguments[0] {
+        Value::Integer(value) => value
+            .checked_abs()
+            .map(Value::Integer)
+            .ok_or_else(|| "integer overflow".to_string()),
+        Value::Float(value) => Ok(Value::Float(value.abs())),
+        value => Err(format!("expected number, got {value}")),
+    });
+    evaluator.register("sqrt", 1, |arguments| match &arguments[0] {
+        Value::Float(value) => Ok(Value::Float(value.sqrt())),
+        value => Err(format!("expected float, got {value}")),
+    });
+    evaluator.register("floor", 1, |arguments| match &arguments[0] {
+        Value::Float(value) => Ok(Value::Float(value.floor())),
+        value => Err(format!("expected float, got {value}")),
+    });
+}
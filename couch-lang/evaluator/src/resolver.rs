@@ -0,0 +1,410 @@
+use std::collections::HashMap;
+
+use couch_lang_parser::{Expression, MatchArm, Node, Parameter, Pattern, Position, Statement};
+
+use crate::{
+    environment::Environment,
+    error::{ErrorKind, RuntimeError},
+    IdentifierType,
+};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Binding {
+    Local { depth: usize, slot: usize },
+    Global,
+}
+
+#[derive(Debug, Default)]
+pub struct Resolution {
+    bindings: HashMap<usize, Binding>,
+}
+
+impl Resolution {
+    pub fn binding(&self, position: &Position) -> Option<Binding> {
+        self.bindings.get(&position.index).copied()
+    }
+}
+
+pub fn resolve(
+    statements: &[Node<Statement>],
+    globals: &Environment,
+) -> Result<Resolution, Vec<RuntimeError>> {
+    let mut resolver = Resolver {
+        scopes: Vec::new(),
+        functions: 0,
+        globals,
+        resolution: Resolution::default(),
+        errors: Vec::new(),
+    };
+    resolver.push(0, &[], statements);
+    for statement in statements {
+        resolver.resolve_statement(statement);
+    }
+    let mut errors = resolver.errors;
+    if errors.is_empty() {
+        return Ok(resolver.resolution);
+    }
+    errors.sort_by_key(|error| error.position.index);
+    Err(errors)
+}
+
+#[derive(Clone, Copy)]
+enum Declaration {
+    Value { mutable: bool },
+    Function,
+    Struct,
+    Enum,
+}
+
+impl From<&IdentifierType> for Declaration {
+    fn from(definition: &IdentifierType) -> Self {
+        match definition {
+            IdentifierType::Value { mutable, .. } => Declaration::Value { mutable: *mutable },
+            IdentifierType::Function { .. } => Declaration::Function,
+            IdentifierType::Struct { .. } => Declaration::Struct,
+            IdentifierType::Enum { .. } => Declaration::Enum,
+        }
+    }
+}
+
+fn declaration(statement: &Statement) -> Option<(&Node<Expression>, Declaration)> {
+    match statement {
+        Statement::Let {
+            mutable,
+            identifier,
+            ..
+        } => Some((identifier, Declaration::Value { mutable: *mutable })),
+        Statement::Function { identifier, .. } => Some((identifier, Declaration::Function)),
+        Statement::Struct { identifier, .. } => Some((identifier, Declaration::Struct)),
+        Statement::Enum { identifier, .. } => Some((identifier, Declaration::Enum)),
+        _ => None,
+    }
+}
+
+struct Scope {
+    function: usize,
+    slots: HashMap<String, (usize, Declaration)>,
+    declared: HashMap<String, Declaration>,
+}
+
+struct Resolver<'a> {
+    scopes: Vec<Scope>,
+    functions: usize,
+    globals: &'a Environment,
+    resolution: Resolution,
+    errors: Vec<RuntimeError>,
+}
+
+impl Resolver<'_> {
+    fn error(&mut self, kind: ErrorKind, message: String, position: &Position) {
+        self.errors.push(RuntimeError {
+            kind,
+            message,
+            position: position.clone(),
+            backtrace: Vec::new(),
+        });
+    }
+    fn push(&mut self, function: usize, parameters: &[Node<Parameter>], body: &[Node<Statement>]) {
+        let mut scope = Scope {
+            function,
+            slots: HashMap::new(),
+            declared: HashMap::new(),
+        };
+        let parameters = parameters
+            .iter()
+            .filter_map(|parameter| match &parameter.value {
+                Parameter::Item {
+                    mutable,
+                    identifier,
+                    ..
+                } => Some((&**identifier, Declaration::Value { mutable: *mutable })),
+                Parameter::Error(_) => None,
+            });
+        let statements = body
+            .iter()
+            .filter_map(|statement| declaration(&statement.value));
+        for (identifier, declaration) in parameters.chain(statements) {
+            if let Expression::Identifier(name) = &identifier.value {
+                let slot = scope.slots.len();
+                scope
+                    .slots
+                    .entry(name.clone())
+                    .or_insert((slot, declaration));
+            }
+        }
+        self.scopes.push(scope);
+    }
+    fn declare(&mut self, identifier: &Node<Expression>, declaration: Declaration) {
+        let Expression::Identifier(name) = &identifier.value else {
+            return;
+        };
+        let scope = self.scopes.last_mut().expect("there is always a scope");
+        if !scope.slots.contains_key(name) {
+            let slot = scope.slots.len();
+            scope.slots.insert(name.clone(), (slot, declaration));
+        }
+        scope.declared.insert(name.clone(), declaration);
+        self.bind(name, &identifier.position);
+    }
+    fn lookup(&self, name: &str) -> Option<(Binding, Declaration)> {
+        let outermost = self.scopes.len() - 1;
+        for (depth, scope) in self.scopes.iter().rev().enumerate() {
+            let declaration = match scope.declared.get(name) {
+                Some(declaration) => *declaration,
+                None if self.functions > scope.function => match scope.slots.get(name) {
+                    Some((_, declaration)) => *declaration,
+                    None => continue,
+                },
+                None => continue,
+            };
+            let binding = match depth {
+                depth if depth == outermost => Binding::Global,
+                depth => Binding::Local {
+                    depth,
+                    slot: scope.slots[name].0,
+                },
+            };
+            return Some((binding, declaration));
+        }
+        let definition = self.globals.get(name)?;
+        Some((Binding::Global, Declaration::from(&definition)))
+    }
+    fn bind(&mut self, name: &str, position: &Position) -> Option<Declaration> {
+        let (binding, declaration) = self.lookup(name)?;
+        self.resolution.bindings.insert(position.index, binding);
+        Some(declaration)
+    }
+    fn reference(&mut self, node: &Node<Expression>, message: &str, position: &Position) {
+        let Expression::Identifier(name) = &node.value else {
+            return self.resolve_expression(node);
+        };
+        if self.bind(name, &node.position).is_none() {
+            self.error(
+                ErrorKind::UndefinedName,
+                format!("identifier {name} {message}"),
+                position,
+            );
+        }
+    }
+    fn resolve_function(&mut self, parameters: &[Node<Parameter>], body: &Node<Expression>) {
+        let Expression::Block(statements) = &body.value else {
+            return self.resolve_expression(body);
+        };
+        self.functions += 1;
+        self.push(self.functions, parameters, statements);
+        for parameter in parameters {
+            if let Parameter::Item {
+                mutable,
+                identifier,
+                ..
+            } = &parameter.value
+            {
+                self.declare(identifier, Declaration::Value { mutable: *mutable });
+            }
+        }
+        for statement in statements {
+            self.resolve_statement(statement);
+        }
+        self.scopes.pop();
+        self.functions -= 1;
+    }
+    fn resolve_block(&mut self, statements: &[Node<Statement>]) {
+        self.push(self.functions, &[], statements);
+        for statement in statements {
+            self.resolve_statement(statement);
+        }
+        self.scopes.pop();
+    }
+    fn resolve_statement(&mut self, node: &Node<Statement>) {
+        match &node.value {
+            Statement::Function {
+                identifier,
+                parameters,
+                body,
+                ..
+            } => {
+                self.declare(identifier, Declaration::Function);
+                self.resolve_function(parameters, body);
+            }
+            Statement::Let {
+                mutable,
+                identifier,
+                value,
+                ..
+            } => {
+                self.resolve_expression(value);
+                self.declare(identifier, Declaration::Value { mutable: *mutable });
+            }
+            Statement::Return(value) => {
+                if self.functions == 0 {
+                    self.error(
+                        ErrorKind::ControlFlow,
+                        "return outside of function".to_string(),
+                        &node.position,
+                    );
+                }
+                if let Some(value) = value {
+                    self.resolve_expression(value);
+                }
+            }
+            Statement::While { condition, body } => {
+                self.resolve_expression(condition);
+                self.resolve_expression(body);
+            }
+            Statement::Struct { identifier, .. } => self.declare(identifier, Declaration::Struct),
+            Statement::Enum { identifier, .. } => self.declare(identifier, Declaration::Enum),
+            Statement::Impl {
+                identifier,
+                methods,
+            } => {
+                self.reference(identifier, "not defined", &identifier.position);
+                for method in methods {
+                    if let Statement::Function {
+                        parameters, body, ..
+                    } = &method.value
+                    {
+                        self.resolve_function(parameters, body);
+                    }
+                }
+            }
+            Statement::Assignment { left, right, .. } => {
+                self.resolve_place(left, &node.position);
+                self.resolve_expression(right);
+            }
+            Statement::Expression(expression) => self.resolve_expression(expression),
+            Statement::Break | Statement::Continue | Statement::Error(_) => {}
+        }
+    }
+    fn resolve_place(&mut self, node: &Node<Expression>, position: &Position) {
+        match &node.value {
+            Expression::Identifier(name) => {
+                let message = match self.bind(name, &node.position) {
+                    Some(Declaration::Value { mutable: true }) => return,
+                    Some(Declaration::Value { .. }) => format!("identifier {name} is not mutable"),
+                    Some(Declaration::Function) => {
+                        "function definitions are not mutable".to_string()
+                    }
+                    Some(Declaration::Struct) => "struct definitions are not mutable".to_string(),
+                    Some(Declaration::Enum) => "enum definitions are not mutable".to_string(),
+                    None => {
+                        return self.error(
+                            ErrorKind::UndefinedName,
+                            format!("identifier {name} not defined"),
+                            position,
+                        )
+                    }
+                };
+                self.error(ErrorKind::Immutable, message, position);
+            }
+            Expression::Index { subject, index } => {
+                self.resolve_place(subject, position);
+                self.resolve_expression(index);
+            }
+            Expression::Member { subject, .. } => self.resolve_place(subject, position),
+            _ => self.resolve_expression(node),
+        }
+    }
+    fn resolve_pattern(&mut self, node: &Node<Pattern>) {
+        match &node.value {
+            Pattern::Identifier(name) => {
+                let identifier = Node {
+                    value: Expression::Identifier(name.clone()),
+                    position: node.position.clone(),
+                };
+                self.declare(&identifier, Declaration::Value { mutable: false });
+            }
+            Pattern::Variant {
+                enumeration,
+                fields,
+                ..
+            } => {
+                self.reference(enumeration, "not defined", &enumeration.position);
+                for field in fields {
+                    self.resolve_pattern(field);
+                }
+            }
+            Pattern::Wildcard | Pattern::Literal(_) | Pattern::Error(_) => {}
+        }
+    }
+    fn resolve_expression(&mut self, node: &Node<Expression>) {
+        match &node.value {
+            Expression::Identifier(_) => {
+                self.reference(node, "is not yet given value", &node.position);
+            }
+            Expression::Call { subject, arguments } => {
+                self.reference(subject, "not defined", &node.position);
+                for argument in arguments {
+                    self.resolve_expression(argument);
+                }
+            }
+            Expression::List(elements) => {
+                for element in elements {
+                    self.resolve_expression(element);
+                }
+            }
+            Expression::Map(entries) => {
+                for (key, value) in entries {
+                    self.resolve_expression(key);
+                    self.resolve_expression(value);
+                }
+            }
+            Expression::Struct { identifier, fields } => {
+                self.reference(identifier, "not defined", &identifier.position);
+                for (_, value) in fields {
+                    self.resolve_expression(value);
+                }
+            }
+            Expression::Index { subject, index } => {
+                self.resolve_expression(subject);
+                self.resolve_expression(index);
+            }
+            Expression::Member { subject, .. } | Expression::Unary { subject, .. } => {
+                self.resolve_expression(subject);
+            }
+            Expression::Path { subject, .. } => {
+                self.reference(subject, "not defined", &subject.position);
+            }
+            Expression::Match { subject, arms } => {
+                self.resolve_expression(subject);
+                for MatchArm {
+                    pattern,
+                    guard,
+                    body,
+                } in arms
+                {
+                    self.push(self.functions, &[], &[]);
+                    self.resolve_pattern(pattern);
+                    if let Some(guard) = guard {
+                        self.resolve_expression(guard);
+                    }
+                    self.resolve_expression(body);
+                    self.scopes.pop();
+                }
+            }
+            Expression::Function {
+                parameters, body, ..
+            } => self.resolve_function(parameters, body),
+            Expression::Block(statements) => self.resolve_block(statements),
+            Expression::If {
+                condition,
+                truthy,
+                falsy,
+            } => {
+                self.resolve_expression(condition);
+                self.resolve_expression(truthy);
+                if let Some(falsy) = falsy {
+                    self.resolve_expression(falsy);
+                }
+            }
+            Expression::Binary { left, right, .. } => {
+                self.resolve_expression(left);
+                self.resolve_expression(right);
+            }
+            Expression::Integer(_)
+            | Expression::Float(_)
+            | Expression::Bool(_)
+            | Expression::String(_)
+            | Expression::Error(_) => {}
+        }
+    }
+}
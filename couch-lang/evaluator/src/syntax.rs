@@ -0,0 +1,235 @@
+use couch_lang_parser::{
+    Expression, MatchArm, Node, Parameter, Pattern, Position, Statement, TypeAnnotation,
+};
+
+use crate::error::{ErrorKind, RuntimeError};
+
+pub(crate) fn check(statements: &[Node<Statement>]) -> Vec<RuntimeError> {
+    let mut errors = Vec::new();
+    for statement in statements {
+        visit_statement(statement, &mut errors);
+    }
+    errors.sort_by_key(|error: &RuntimeError| error.position.index);
+    errors
+}
+
+fn report(message: &str, position: &Position, errors: &mut Vec<RuntimeError>) {
+    errors.push(RuntimeError {
+        kind: ErrorKind::Syntax,
+        message: message.to_string(),
+        position: position.clone(),
+        backtrace: Vec::new(),
+    });
+}
+
+fn visit_function(
+    parameters: &[Node<Parameter>],
+    return_type: &Option<Box<Node<TypeAnnotation>>>,
+    body: &Node<Expression>,
+    errors: &mut Vec<RuntimeError>,
+) {
+    for parameter in parameters {
+        match &parameter.value {
+            Parameter::Item { annotation, .. } => {
+                if let Some(annotation) = annotation {
+                    visit_annotation(annotation, errors);
+                }
+            }
+            Parameter::Error(message) => report(message, &parameter.position, errors),
+        }
+    }
+    if let Some(return_type) = return_type {
+        visit_annotation(return_type, errors);
+    }
+    visit_expression(body, errors);
+}
+
+fn visit_annotation(node: &Node<TypeAnnotation>, errors: &mut Vec<RuntimeError>) {
+    match &node.value {
+        TypeAnnotation::Named(_) => {}
+        TypeAnnotation::List(element) | TypeAnnotation::Map(element) => {
+            visit_annotation(element, errors)
+        }
+        TypeAnnotation::Function {
+            parameters,
+            return_type,
+        } => {
+            for parameter in parameters {
+                visit_annotation(parameter, errors);
+            }
+            visit_annotation(return_type, errors);
+        }
+        TypeAnnotation::Error(message) => report(message, &node.position, errors),
+    }
+}
+
+fn visit_statement(node: &Node<Statement>, errors: &mut Vec<RuntimeError>) {
+    match &node.value {
+        Statement::Function {
+            identifier,
+            parameters,
+            return_type,
+            body,
+        } => {
+            visit_expression(identifier, errors);
+            visit_function(parameters, return_type, body, errors);
+        }
+        Statement::Let {
+            identifier,
+            annotation,
+            value,
+            ..
+        } => {
+            visit_expression(identifier, errors);
+            if let Some(annotation) = annotation {
+                visit_annotation(annotation, errors);
+            }
+            visit_expression(value, errors);
+        }
+        Statement::Return(value) => {
+            if let Some(value) = value {
+                visit_expression(value, errors);
+            }
+        }
+        Statement::While { condition, body } => {
+            visit_expression(condition, errors);
+            visit_expression(body, errors);
+        }
+        Statement::Struct { identifier, fields } => {
+            visit_expression(identifier, errors);
+            for field in fields {
+                visit_expression(field, errors);
+            }
+        }
+        Statement::Enum {
+            identifier,
+            variants,
+        } => {
+            visit_expression(identifier, errors);
+            for (variant, fields) in variants {
+                visit_expression(variant, errors);
+                for field in fields {
+                    visit_expression(field, errors);
+                }
+            }
+        }
+        Statement::Impl {
+            identifier,
+            methods,
+        } => {
+            visit_expression(identifier, errors);
+            for method in methods {
+                visit_statement(method, errors);
+            }
+        }
+        Statement::Assignment { left, right, .. } => {
+            visit_expression(left, errors);
+            visit_expression(right, errors);
+        }
+        Statement::Expression(expression) => visit_expression(expression, errors),
+        Statement::Error(message) => report(message, &node.position, errors),
+        Statement::Break | Statement::Continue => {}
+    }
+}
+
+fn visit_pattern(node: &Node<Pattern>, errors: &mut Vec<RuntimeError>) {
+    match &node.value {
+        Pattern::Variant {
+            enumeration,
+            variant,
+            fields,
+        } => {
+            visit_expression(enumeration, errors);
+            visit_expression(variant, errors);
+            for field in fields {
+                visit_pattern(field, errors);
+            }
+        }
+        Pattern::Error(message) => report(message, &node.position, errors),
+        Pattern::Wildcard | Pattern::Identifier(_) | Pattern::Literal(_) => {}
+    }
+}
+
+fn visit_expression(node: &Node<Expression>, errors: &mut Vec<RuntimeError>) {
+    match &node.value {
+        Expression::Call { subject, arguments } => {
+            visit_expression(subject, errors);
+            for argument in arguments {
+                visit_expression(argument, errors);
+            }
+        }
+        Expression::List(elements) => {
+            for element in elements {
+                visit_expression(element, errors);
+            }
+        }
+        Expression::Index { subject, index } => {
+            visit_expression(subject, errors);
+            visit_expression(index, errors);
+        }
+        Expression::Map(entries) => {
+            for (key, value) in entries {
+                visit_expression(key, errors);
+                visit_expression(value, errors);
+            }
+        }
+        Expression::Struct { identifier, fields } => {
+            visit_expression(identifier, errors);
+            for (field, value) in fields {
+                visit_expression(field, errors);
+                visit_expression(value, errors);
+            }
+        }
+        Expression::Member { subject, member } | Expression::Path { subject, member } => {
+            visit_expression(subject, errors);
+            visit_expression(member, errors);
+        }
+        Expression::Unary { subject, .. } => visit_expression(subject, errors),
+        Expression::Match { subject, arms } => {
+            visit_expression(subject, errors);
+            for MatchArm {
+                pattern,
+                guard,
+                body,
+            } in arms
+            {
+                visit_pattern(pattern, errors);
+                if let Some(guard) = guard {
+                    visit_expression(guard, errors);
+                }
+                visit_expression(body, errors);
+            }
+        }
+        Expression::Function {
+            parameters,
+            return_type,
+            body,
+        } => visit_function(parameters, return_type, body, errors),
+        Expression::Block(statements) => {
+            for statement in statements {
+                visit_statement(statement, errors);
+            }
+        }
+        Expression::If {
+            condition,
+            truthy,
+            falsy,
+        } => {
+            visit_expression(condition, errors);
+            visit_expression(truthy, errors);
+            if let Some(falsy) = falsy {
+                visit_expression(falsy, errors);
+            }
+        }
+        Expression::Binary { left, right, .. } => {
+            visit_expression(left, errors);
+            visit_expression(right, errors);
+        }
+        Expression::Error(message) => report(message, &node.position, errors),
+        Expression::Integer(_)
+        | Expression::Float(_)
+        | Expression::Bool(_)
+        | Expression::String(_)
+        | Expression::Identifier(_) => {}
+    }
+}
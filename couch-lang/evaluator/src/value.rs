@@ -1,18 +1,272 @@
 use std::{
-    fmt::Display,
-    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Sub, SubAssign},
+    cell::RefCell,
+    collections::{BTreeMap, HashMap},
+    fmt::{Debug, Display},
+    ops::{Add, BitAnd, BitOr, BitXor, Deref, Div, Mul, Rem, Shl, Shr, Sub},
+    rc::Rc,
 };
 
-#[derive(Debug, PartialEq, Clone)]
+use couch_lang_parser::{AssignmentVariant, Expression, Node, Parameter};
+
+use crate::{environment::Environment, error::ErrorKind, resolver::Resolution};
+
+pub struct Function {
+    pub name: Option<String>,
+    pub parameters: Vec<Node<Parameter>>,
+    pub body: Node<Expression>,
+    pub environment: Environment,
+    pub resolution: Rc<Resolution>,
+}
+
+/// Shared data that can only be written through a mutable reference.
+/// Binding a value without `mut` freezes its references, and whatever is
+/// read through a frozen reference is frozen too.
+pub struct Reference<T> {
+    target: Rc<T>,
+    mutable: bool,
+}
+
+impl<T> Reference<T> {
+    pub fn new(target: T) -> Self {
+        Self {
+            target: Rc::new(target),
+            mutable: true,
+        }
+    }
+    pub fn mutable(&self) -> bool {
+        self.mutable
+    }
+    pub fn frozen(&self) -> Self {
+        Self {
+            target: Rc::clone(&self.target),
+            mutable: false,
+        }
+    }
+    pub fn ptr_eq(a: &Self, b: &Self) -> bool {
+        Rc::ptr_eq(&a.target, &b.target)
+    }
+    fn pointer(&self) -> *const () {
+        Rc::as_ptr(&self.target) as *const ()
+    }
+}
+
+impl<T> Clone for Reference<T> {
+    fn clone(&self) -> Self {
+        Self {
+            target: Rc::clone(&self.target),
+            mutable: self.mutable,
+        }
+    }
+}
+
+impl<T> Deref for Reference<T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        &self.target
+    }
+}
+
+impl<T: Debug> Debug for Reference<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.target.fmt(f)
+    }
+}
+
+pub type List = Reference<RefCell<Vec<Value>>>;
+
+pub type Map = Reference<RefCell<BTreeMap<String, Value>>>;
+
+pub type NativeFn = dyn Fn(&[Value]) -> Result<Value, String>;
+
+pub struct NativeFunction {
+    pub name: String,
+    pub arity: usize,
+    pub function: Box<NativeFn>,
+}
+
+impl PartialEq for NativeFunction {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Debug for NativeFunction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("NativeFunction")
+            .field("name", &self.name)
+            .field("arity", &self.arity)
+            .finish()
+    }
+}
+
+pub struct StructType {
+    pub name: String,
+    pub fields: Vec<String>,
+    pub methods: RefCell<HashMap<String, Rc<Function>>>,
+}
+
+impl PartialEq for StructType {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Debug for StructType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StructType")
+            .field("name", &self.name)
+            .field("fields", &self.fields)
+            .finish()
+    }
+}
+
+pub struct Instance {
+    pub definition: Rc<StructType>,
+    pub fields: RefCell<Vec<Value>>,
+}
+
+impl Debug for Instance {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_struct(&self.definition.name);
+        for (name, value) in self
+            .definition
+            .fields
+            .iter()
+            .zip(self.fields.borrow().iter())
+        {
+            debug.field(name, value);
+        }
+        debug.finish()
+    }
+}
+
+pub struct EnumType {
+    pub name: String,
+    pub variants: Vec<(String, usize)>,
+}
+
+impl PartialEq for EnumType {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Debug for EnumType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EnumType")
+            .field("name", &self.name)
+            .field("variants", &self.variants)
+            .finish()
+    }
+}
+
+pub struct Variant {
+    pub definition: Rc<EnumType>,
+    pub variant: usize,
+    pub fields: Vec<Value>,
+}
+
+impl Variant {
+    pub fn name(&self) -> String {
+        let (variant, _) = &self.definition.variants[self.variant];
+        format!("{}::{variant}", self.definition.name)
+    }
+}
+
+impl PartialEq for Variant {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.definition, &other.definition)
+            && self.variant == other.variant
+            && self.fields == other.fields
+    }
+}
+
+impl Debug for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut debug = f.debug_tuple(&self.name());
+        for value in &self.fields {
+            debug.field(value);
+        }
+        debug.finish()
+    }
+}
+
+impl PartialEq for Function {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self, other)
+    }
+}
+
+impl Debug for Function {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Function")
+            .field("name", &self.name)
+            .field("parameters", &self.parameters.len())
+            .finish()
+    }
+}
+
+#[derive(Clone)]
 pub enum Value {
     Integer(i64),
     Float(f64),
     Bool(bool),
-    Error {
-        message: String,
-        line: usize,
-        column: usize,
-    },
+    String(String),
+    List(List),
+    Map(Map),
+    Struct(Reference<Instance>),
+    Enum(Rc<Variant>),
+    Function(Rc<Function>),
+    NativeFunction(Rc<NativeFunction>),
+    Unit,
+}
+
+thread_local! {
+    static DEBUGGING: RefCell<Vec<*const ()>> = const { RefCell::new(Vec::new()) };
+}
+
+fn debug_once(
+    f: &mut std::fmt::Formatter<'_>,
+    name: &str,
+    pointer: *const (),
+    value: &dyn Debug,
+    cycle: &str,
+) -> std::fmt::Result {
+    if DEBUGGING.with(|visiting| visiting.borrow().contains(&pointer)) {
+        return write!(f, "{name}({cycle})");
+    }
+    DEBUGGING.with(|visiting| visiting.borrow_mut().push(pointer));
+    let result = f.debug_tuple(name).field(value).finish();
+    DEBUGGING.with(|visiting| visiting.borrow_mut().pop());
+    result
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        self.equals(other, &mut Vec::new())
+    }
+}
+
+impl Debug for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Integer(value) => f.debug_tuple("Integer").field(value).finish(),
+            Value::Float(value) => f.debug_tuple("Float").field(value).finish(),
+            Value::Bool(value) => f.debug_tuple("Bool").field(value).finish(),
+            Value::String(value) => f.debug_tuple("String").field(value).finish(),
+            Value::List(list) => debug_once(f, "List", list.pointer(), list, "[...]"),
+            Value::Map(map) => debug_once(f, "Map", map.pointer(), map, "{...}"),
+            Value::Struct(instance) => {
+                debug_once(f, "Struct", instance.pointer(), instance, "{...}")
+            }
+            Value::Enum(variant) => f.debug_tuple("Enum").field(variant).finish(),
+            Value::Function(function) => f.debug_tuple("Function").field(function).finish(),
+            Value::NativeFunction(function) => {
+                f.debug_tuple("NativeFunction").field(function).finish()
+            }
+            Value::Unit => f.write_str("Unit"),
+        }
+    }
 }
 
 impl Display for Value {
@@ -21,20 +275,48 @@ impl Display for Value {
             Value::Integer(_) => f.write_str("integer"),
             Value::Float(_) => f.write_str("float"),
             Value::Bool(_) => f.write_str("bool"),
-            Value::Error { .. } => f.write_str("error"),
+            Value::String(_) => f.write_str("string"),
+            Value::List(_) => f.write_str("list"),
+            Value::Map(_) => f.write_str("map"),
+            Value::Struct(instance) => f.write_str(&instance.definition.name),
+            Value::Enum(variant) => f.write_str(&variant.definition.name),
+            Value::Function(_) | Value::NativeFunction(_) => f.write_str("function"),
+            Value::Unit => f.write_str("unit"),
         }
     }
 }
 
+pub type OperationResult<T> = Result<T, (ErrorKind, String)>;
+
+fn no_implementation(a: &Value, op: &str, b: &Value) -> (ErrorKind, String) {
+    (
+        ErrorKind::Type,
+        format!("no implementation exists for {a} {op} {b}"),
+    )
+}
+
+fn checked_integer(result: Option<i64>, rhs: i64) -> OperationResult<i64> {
+    result.ok_or_else(|| match rhs {
+        0 => (ErrorKind::Arithmetic, "division by zero".to_string()),
+        _ => (ErrorKind::Arithmetic, "integer overflow".to_string()),
+    })
+}
+
+/// Like `checked_rem`, but `i64::MIN % -1` is 0 rather than an overflow.
+fn checked_remainder(a: i64, b: i64) -> Option<i64> {
+    (b != 0).then(|| a.wrapping_rem(b))
+}
+
 macro_rules! implement_operator {
-    ($func_trait:ident, $func_name:ident, $op:tt) => {
+    ($func_trait:ident, $func_name:ident, $op:tt, $checked:path $(, $pattern:pat => $result:expr)*) => {
         impl $func_trait for Value {
-            type Output = Result<Value, String>;
+            type Output = OperationResult<Value>;
             fn $func_name(self, rhs: Self) -> Self::Output {
                 match (self, rhs) {
-                    (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a $op b)),
+                    (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(checked_integer($checked(a, b), b)?)),
                     (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a $op b)),
-                    (a, b) => Err(format!("no implementation exists for {a} {} {b}", stringify!($op))),
+                    $($pattern => Ok($result),)*
+                    (a, b) => Err(no_implementation(&a, stringify!($op), &b)),
                 }
             }
         }
@@ -42,25 +324,313 @@ macro_rules! implement_operator {
 }
 
 macro_rules! implement_operator_assign {
+    ($func_name:ident, $op:tt, $checked:path $(, $pattern:pat => $result:expr)*) => {
+        impl Value {
+            pub fn $func_name(&mut self, rhs: Self) -> OperationResult<()> {
+                match (self, rhs) {
+                    (Value::Integer(a), Value::Integer(b)) => *a = checked_integer($checked(*a, b), b)?,
+                    (Value::Float(a), Value::Float(b)) => *a $op b,
+                    $($pattern => $result,)*
+                    (a, b) => return Err(no_implementation(a, stringify!($op), &b)),
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+macro_rules! implement_integer_operator {
     ($func_trait:ident, $func_name:ident, $op:tt) => {
         impl $func_trait for Value {
-            fn $func_name(&mut self, rhs: Self) {
+            type Output = OperationResult<Value>;
+            fn $func_name(self, rhs: Self) -> Self::Output {
                 match (self, rhs) {
-                    (Value::Integer(a), Value::Integer(b)) => {*a $op b},
-                    (Value::Float(a), Value::Float(b)) => {*a $op b},
-                    (a, b) => panic!("no implementation exists for {a} {}= {b}", stringify!($op)),
+                    (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(a $op b)),
+                    (a, b) => Err(no_implementation(&a, stringify!($op), &b)),
                 }
             }
         }
     };
 }
 
-implement_operator!(Add, add, +);
-implement_operator!(Sub, sub, -);
-implement_operator!(Mul, mul, *);
-implement_operator!(Div, div, /);
+macro_rules! implement_shift {
+    ($func_trait:ident, $func_name:ident, $op:tt, $checked:ident, $out_of_range:expr) => {
+        impl $func_trait for Value {
+            type Output = OperationResult<Value>;
+            fn $func_name(self, rhs: Self) -> Self::Output {
+                match (self, rhs) {
+                    (Value::Integer(_), Value::Integer(b)) if b < 0 => Err((
+                        ErrorKind::Arithmetic,
+                        format!("cannot shift by negative amount {b}"),
+                    )),
+                    (Value::Integer(a), Value::Integer(b)) => Ok(Value::Integer(
+                        u32::try_from(b)
+                            .ok()
+                            .and_then(|b| a.$checked(b))
+                            .unwrap_or_else(|| $out_of_range(a)),
+                    )),
+                    (a, b) => Err(no_implementation(&a, stringify!($op), &b)),
+                }
+            }
+        }
+    };
+}
+
+macro_rules! implement_comparison {
+    ($func_name:ident, $op:tt) => {
+        impl Value {
+            pub fn $func_name(self, rhs: Self) -> OperationResult<Value> {
+                match (self, rhs) {
+                    (Value::Integer(a), Value::Integer(b)) => Ok(Value::Bool(a $op b)),
+                    (Value::Float(a), Value::Float(b)) => Ok(Value::Bool(a $op b)),
+                    (a, b) => Err(no_implementation(&a, stringify!($op), &b)),
+                }
+            }
+        }
+    };
+}
+
+implement_operator!(Add, add, +, i64::checked_add, (Value::String(a), Value::String(b)) => Value::String(a + &b));
+implement_operator!(Sub, sub, -, i64::checked_sub);
+implement_operator!(Mul, mul, *, i64::checked_mul);
+implement_operator!(Div, div, /, i64::checked_div);
+implement_operator!(Rem, rem, %, checked_remainder);
+
+implement_integer_operator!(BitAnd, bitand, &);
+implement_integer_operator!(BitOr, bitor, |);
+implement_integer_operator!(BitXor, bitxor, ^);
+
+implement_shift!(Shl, shl, <<, checked_shl, |_| 0);
+implement_shift!(Shr, shr, >>, checked_shr, |a: i64| if a < 0 { -1 } else { 0 });
+
+implement_operator_assign!(add_assign, +=, i64::checked_add, (Value::String(a), Value::String(b)) => a.push_str(&b));
+implement_operator_assign!(sub_assign, -=, i64::checked_sub);
+implement_operator_assign!(mul_assign, *=, i64::checked_mul);
+implement_operator_assign!(div_assign, /=, i64::checked_div);
+implement_operator_assign!(rem_assign, %=, checked_remainder);
+
+implement_comparison!(less, <);
+implement_comparison!(less_equal, <=);
+implement_comparison!(greater, >);
+implement_comparison!(greater_equal, >=);
+
+impl Value {
+    pub fn assign(&mut self, variant: &AssignmentVariant, rhs: Self) -> OperationResult<()> {
+        match variant {
+            AssignmentVariant::Base => {
+                *self = rhs;
+                Ok(())
+            }
+            AssignmentVariant::Addition => self.add_assign(rhs),
+            AssignmentVariant::Subtraction => self.sub_assign(rhs),
+            AssignmentVariant::Multiplication => self.mul_assign(rhs),
+            AssignmentVariant::Division => self.div_assign(rhs),
+            AssignmentVariant::Modulo => self.rem_assign(rhs),
+        }
+    }
+}
+
+impl Value {
+    pub fn pow(self, rhs: Self) -> OperationResult<Value> {
+        match (self, rhs) {
+            (Value::Integer(_), Value::Integer(b)) if b < 0 => Err((
+                ErrorKind::Arithmetic,
+                format!("cannot raise integer to negative power {b}"),
+            )),
+            (Value::Integer(a), Value::Integer(b)) => u32::try_from(b)
+                .ok()
+                .and_then(|b| a.checked_pow(b))
+                .map(Value::Integer)
+                .ok_or_else(|| (ErrorKind::Arithmetic, "integer overflow".to_string())),
+            (Value::Float(a), Value::Float(b)) => Ok(Value::Float(a.powf(b))),
+            (a, b) => Err(no_implementation(&a, "**", &b)),
+        }
+    }
+}
+
+impl Value {
+    pub fn to_integer(self) -> Result<Value, String> {
+        match self {
+            Value::Integer(value) => Ok(Value::Integer(value)),
+            Value::Float(value) if value.is_nan() => {
+                Err("cannot convert NaN to integer".to_string())
+            }
+            Value::Float(value) => {
+                let truncated = value.trunc();
+                if (i64::MIN as f64..-(i64::MIN as f64)).contains(&truncated) {
+                    Ok(Value::Integer(truncated as i64))
+                } else {
+                    Err(format!("float {value} is out of range for integer"))
+                }
+            }
+            value => Err(format!("cannot convert {value} to integer")),
+        }
+    }
+
+    pub fn to_float(self) -> Result<Value, String> {
+        match self {
+            Value::Integer(value) => Ok(Value::Float(value as f64)),
+            Value::Float(value) => Ok(Value::Float(value)),
+            value => Err(format!("cannot convert {value} to float")),
+        }
+    }
+}
+
+impl Value {
+    pub fn frozen(self) -> Value {
+        match self {
+            Value::List(list) => Value::List(list.frozen()),
+            Value::Map(map) => Value::Map(map.frozen()),
+            Value::Struct(instance) => Value::Struct(instance.frozen()),
+            Value::Enum(variant) => Value::Enum(Rc::new(Variant {
+                definition: Rc::clone(&variant.definition),
+                variant: variant.variant,
+                fields: variant.fields.iter().cloned().map(Value::frozen).collect(),
+            })),
+            value => value,
+        }
+    }
+}
 
-implement_operator_assign!(AddAssign, add_assign, +=);
-implement_operator_assign!(SubAssign, sub_assign, -=);
-implement_operator_assign!(MulAssign, mul_assign, *=);
-implement_operator_assign!(DivAssign, div_assign, /=);
+impl Value {
+    fn equals(&self, other: &Value, visiting: &mut Vec<(*const (), *const ())>) -> bool {
+        match (self, other) {
+            (Value::Integer(a), Value::Integer(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::List(a), Value::List(b)) => {
+                let pair = (a.pointer(), b.pointer());
+                if Reference::ptr_eq(a, b) || visiting.contains(&pair) {
+                    return true;
+                }
+                visiting.push(pair);
+                let (a, b) = (a.borrow(), b.borrow());
+                let equal = a.len() == b.len()
+                    && a.iter().zip(b.iter()).all(|(a, b)| a.equals(b, visiting));
+                visiting.pop();
+                equal
+            }
+            (Value::Map(a), Value::Map(b)) => {
+                let pair = (a.pointer(), b.pointer());
+                if Reference::ptr_eq(a, b) || visiting.contains(&pair) {
+                    return true;
+                }
+                visiting.push(pair);
+                let (a, b) = (a.borrow(), b.borrow());
+                let equal = a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|((a_key, a), (b_key, b))| a_key == b_key && a.equals(b, visiting));
+                visiting.pop();
+                equal
+            }
+            (Value::Struct(a), Value::Struct(b)) => {
+                let pair = (a.pointer(), b.pointer());
+                if Reference::ptr_eq(a, b) || visiting.contains(&pair) {
+                    return true;
+                }
+                if !Rc::ptr_eq(&a.definition, &b.definition) {
+                    return false;
+                }
+                visiting.push(pair);
+                let (a, b) = (a.fields.borrow(), b.fields.borrow());
+                let equal = a.iter().zip(b.iter()).all(|(a, b)| a.equals(b, visiting));
+                visiting.pop();
+                equal
+            }
+            (Value::Enum(a), Value::Enum(b)) => {
+                Rc::ptr_eq(&a.definition, &b.definition)
+                    && a.variant == b.variant
+                    && a.fields
+                        .iter()
+                        .zip(b.fields.iter())
+                        .all(|(a, b)| a.equals(b, visiting))
+            }
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            (Value::NativeFunction(a), Value::NativeFunction(b)) => Rc::ptr_eq(a, b),
+            (Value::Unit, Value::Unit) => true,
+            _ => false,
+        }
+    }
+    pub fn to_printable(&self) -> String {
+        self.printable(&mut Vec::new())
+    }
+    fn printable(&self, visiting: &mut Vec<*const ()>) -> String {
+        match self {
+            Value::Integer(value) => value.to_string(),
+            Value::Float(value) => format!("{value:?}"),
+            Value::Bool(value) => value.to_string(),
+            Value::String(value) => value.clone(),
+            Value::List(elements) => {
+                let pointer = elements.pointer();
+                if visiting.contains(&pointer) {
+                    return "[...]".to_string();
+                }
+                visiting.push(pointer);
+                let elements: Vec<String> = elements
+                    .borrow()
+                    .iter()
+                    .map(|value| value.nested_printable(visiting))
+                    .collect();
+                visiting.pop();
+                format!("[{}]", elements.join(", "))
+            }
+            Value::Map(entries) => {
+                let pointer = entries.pointer();
+                if visiting.contains(&pointer) {
+                    return "{...}".to_string();
+                }
+                visiting.push(pointer);
+                let entries: Vec<String> = entries
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| format!("{key:?}: {}", value.nested_printable(visiting)))
+                    .collect();
+                visiting.pop();
+                format!("{{{}}}", entries.join(", "))
+            }
+            Value::Struct(instance) => {
+                let pointer = instance.pointer();
+                if visiting.contains(&pointer) {
+                    return format!("{} {{...}}", instance.definition.name);
+                }
+                visiting.push(pointer);
+                let fields: Vec<String> = instance
+                    .definition
+                    .fields
+                    .iter()
+                    .zip(instance.fields.borrow().iter())
+                    .map(|(name, value)| format!("{name}: {}", value.nested_printable(visiting)))
+                    .collect();
+                visiting.pop();
+                if fields.is_empty() {
+                    format!("{} {{}}", instance.definition.name)
+                } else {
+                    format!("{} {{ {} }}", instance.definition.name, fields.join(", "))
+                }
+            }
+            Value::Enum(variant) if variant.fields.is_empty() => variant.name(),
+            Value::Enum(variant) => {
+                let fields: Vec<String> = variant
+                    .fields
+                    .iter()
+                    .map(|value| value.nested_printable(visiting))
+                    .collect();
+                format!("{}({})", variant.name(), fields.join(", "))
+            }
+            Value::Function(function) => match &function.name {
+                Some(name) => format!("<function {name}>"),
+                None => "<function>".to_string(),
+            },
+            Value::NativeFunction(function) => format!("<function {}>", function.name),
+            Value::Unit => "()".to_string(),
+        }
+    }
+    fn nested_printable(&self, visiting: &mut Vec<*const ()>) -> String {
+        match self {
+            Value::String(value) => format!("{value:?}"),
+            value => value.printable(visiting),
+        }
+    }
+}
@@ -40,7 +40,7 @@ where
             line: self.line,
             column: self.column,
         };
-        self.index += 1;
+        self.index += value.len_utf8();
         if value == '\n' {
             self.column = 1;
             self.line += 1;
@@ -76,6 +76,17 @@ where
                 "let" => TokenVariant::LetKeyword,
                 "mut" => TokenVariant::MutKeyword,
                 "return" => TokenVariant::ReturnKeyword,
+                "while" => TokenVariant::WhileKeyword,
+                "break" => TokenVariant::BreakKeyword,
+                "continue" => TokenVariant::ContinueKeyword,
+                "if" => TokenVariant::IfKeyword,
+                "else" => TokenVariant::ElseKeyword,
+                "true" => TokenVariant::TrueKeyword,
+                "false" => TokenVariant::FalseKeyword,
+                "struct" => TokenVariant::StructKeyword,
+                "impl" => TokenVariant::ImplKeyword,
+                "enum" => TokenVariant::EnumKeyword,
+                "match" => TokenVariant::MatchKeyword,
                 _ => TokenVariant::Identifier,
             };
             Token {
@@ -89,7 +100,7 @@ where
 
         loop {
             let Some(IndexedChar { value, .. }) = self.iter.peek() else {
-                break make_token(text)
+                break make_token(text);
             };
             match value {
                 '0'..='9' | 'a'..='z' | 'A'..='Z' | '_' => {
@@ -176,6 +187,36 @@ where
         }
     }
 
+    fn make_single_or_either_double_token(
+        &mut self,
+        single_variant: TokenVariant,
+        (first_char, first_variant): (char, TokenVariant),
+        (second_char, second_variant): (char, TokenVariant),
+    ) -> Token {
+        let IndexedChar {
+            index,
+            line,
+            column,
+            ..
+        } = self.iter.next().expect(NO_MUT_PEEK_NEXT_MESSAGE);
+
+        let (length, variant) = match self.iter.peek() {
+            Some(IndexedChar { value, .. }) if *value == first_char => (2, first_variant),
+            Some(IndexedChar { value, .. }) if *value == second_char => (2, second_variant),
+            Some(_) | None => (1, single_variant),
+        };
+        if length == 2 {
+            self.iter.next();
+        }
+        Token {
+            index,
+            column,
+            line,
+            length,
+            variant,
+        }
+    }
+
     fn make_number(&mut self) -> Token {
         let IndexedChar {
             index,
@@ -240,10 +281,45 @@ where
         }
     }
 
+    fn make_string(&mut self) -> Token {
+        let IndexedChar {
+            index,
+            line,
+            column,
+            ..
+        } = self.iter.next().expect(NO_MUT_PEEK_NEXT_MESSAGE);
+        let mut length = 1;
+        let mut escaped = false;
+
+        loop {
+            let Some(IndexedChar { value, .. }) = self.iter.next() else {
+                break Token {
+                    variant: TokenVariant::UnterminatedString,
+                    index,
+                    length,
+                    line,
+                    column,
+                };
+            };
+            length += value.len_utf8();
+            match value {
+                '"' if !escaped => {
+                    break Token {
+                        variant: TokenVariant::String,
+                        index,
+                        length,
+                        line,
+                        column,
+                    }
+                }
+                '\\' if !escaped => escaped = true,
+                _ => escaped = false,
+            }
+        }
+    }
+
     fn make_token(&mut self) -> Option<Token> {
-        let Some(char) = self.iter.peek() else {
-            return None;
-        };
+        let char = self.iter.peek()?;
 
         let token = match char.value {
             '0'..='9' => self.make_number(),
@@ -251,33 +327,69 @@ where
                 self.iter.next();
                 self.make_token()?
             }
-            '=' => self.make_single_or_double_token(
+            '=' => self.make_single_or_either_double_token(
                 TokenVariant::Equal,
-                '=',
-                TokenVariant::DoubleEqual,
+                ('=', TokenVariant::DoubleEqual),
+                ('>', TokenVariant::EqualGreater),
             ),
             ';' => self.make_single_token(TokenVariant::Semicolon),
+            ',' => self.make_single_token(TokenVariant::Comma),
             '(' => self.make_single_token(TokenVariant::LParenthesis),
             ')' => self.make_single_token(TokenVariant::RParenthesis),
             '{' => self.make_single_token(TokenVariant::LBrace),
             '}' => self.make_single_token(TokenVariant::RBrace),
-            '-' => {
-                self.make_single_or_double_token(TokenVariant::Minus, '=', TokenVariant::MinusEqual)
-            }
+            '[' => self.make_single_token(TokenVariant::LBracket),
+            ']' => self.make_single_token(TokenVariant::RBracket),
+            ':' => self.make_single_or_double_token(
+                TokenVariant::Colon,
+                ':',
+                TokenVariant::DoubleColon,
+            ),
+            '.' => self.make_single_token(TokenVariant::Dot),
+            '-' => self.make_single_or_either_double_token(
+                TokenVariant::Minus,
+                ('=', TokenVariant::MinusEqual),
+                ('>', TokenVariant::MinusGreater),
+            ),
             '!' => self.make_single_or_double_token(
                 TokenVariant::Exclamation,
                 '=',
                 TokenVariant::ExclamationEqual,
             ),
-            '*' => self.make_single_or_double_token(
+            '*' => self.make_single_or_either_double_token(
                 TokenVariant::Asterisk,
+                ('=', TokenVariant::AsteriskEqual),
+                ('*', TokenVariant::DoubleAsterisk),
+            ),
+            '%' => self.make_single_or_double_token(
+                TokenVariant::Percent,
                 '=',
-                TokenVariant::AsteriskEqual,
+                TokenVariant::PercentEqual,
             ),
             '+' => {
                 self.make_single_or_double_token(TokenVariant::Plus, '=', TokenVariant::PlusEqual)
             }
+            '<' => self.make_single_or_either_double_token(
+                TokenVariant::Less,
+                ('=', TokenVariant::LessEqual),
+                ('<', TokenVariant::DoubleLess),
+            ),
+            '>' => self.make_single_or_either_double_token(
+                TokenVariant::Greater,
+                ('=', TokenVariant::GreaterEqual),
+                ('>', TokenVariant::DoubleGreater),
+            ),
+            '&' => self.make_single_or_double_token(
+                TokenVariant::Ampersand,
+                '&',
+                TokenVariant::DoubleAmpersand,
+            ),
+            '|' => {
+                self.make_single_or_double_token(TokenVariant::Pipe, '|', TokenVariant::DoublePipe)
+            }
+            '^' => self.make_single_token(TokenVariant::Caret),
             'a'..='z' | 'A'..='Z' | '_' => self.make_keyword_or_identifier(),
+            '"' => self.make_string(),
             '/' => self.make_comment_or_slash()?,
             _ => {
                 let token = Token {
@@ -385,7 +497,7 @@ mod tests {
             factory.make("5.", TokenVariant::Float),
         ]
         .into_iter()
-        .filter_map(|option| option)
+        .flatten()
         .collect();
 
         assert_eq!(lexer.into_iter().collect::<Vec<Token>>(), tokens);
@@ -420,7 +532,7 @@ mod tests {
             factory.make(";", TokenVariant::Semicolon),
         ]
         .into_iter()
-        .filter_map(|option| option)
+        .flatten()
         .collect();
 
         assert_eq!(lexer.into_iter().collect::<Vec<Token>>(), tokens);
@@ -429,7 +541,8 @@ mod tests {
     #[test]
     fn all_tokens() {
         let input =
-            String::from("let mut fn return a ( ) { } = += + -= - *= * /= / ; 100 100.0 ! != == Å");
+            r#"let mut fn return while break continue if else true false struct impl enum match a ( ) { } = += + -= - *= * /= / ; , 100 100.0 "s" ! != == < <= > >= && || % %= ** & | ^ << >> [ ] : . :: => -> Å"#
+                .to_string();
 
         let lexer = Lexer::new(input.chars());
         let mut factory = TokenFactory::new();
@@ -445,6 +558,28 @@ mod tests {
             factory.skip(" "),
             factory.make("return", ReturnKeyword),
             factory.skip(" "),
+            factory.make("while", WhileKeyword),
+            factory.skip(" "),
+            factory.make("break", BreakKeyword),
+            factory.skip(" "),
+            factory.make("continue", ContinueKeyword),
+            factory.skip(" "),
+            factory.make("if", IfKeyword),
+            factory.skip(" "),
+            factory.make("else", ElseKeyword),
+            factory.skip(" "),
+            factory.make("true", TrueKeyword),
+            factory.skip(" "),
+            factory.make("false", FalseKeyword),
+            factory.skip(" "),
+            factory.make("struct", StructKeyword),
+            factory.skip(" "),
+            factory.make("impl", ImplKeyword),
+            factory.skip(" "),
+            factory.make("enum", EnumKeyword),
+            factory.skip(" "),
+            factory.make("match", MatchKeyword),
+            factory.skip(" "),
             factory.make("a", Identifier),
             factory.skip(" "),
             factory.make("(", LParenthesis),
@@ -475,20 +610,66 @@ mod tests {
             factory.skip(" "),
             factory.make(";", Semicolon),
             factory.skip(" "),
+            factory.make(",", Comma),
+            factory.skip(" "),
             factory.make("100", Integer),
             factory.skip(" "),
             factory.make("100.0", Float),
             factory.skip(" "),
+            factory.make(r#""s""#, String),
+            factory.skip(" "),
             factory.make("!", Exclamation),
             factory.skip(" "),
             factory.make("!=", ExclamationEqual),
             factory.skip(" "),
             factory.make("==", DoubleEqual),
             factory.skip(" "),
+            factory.make("<", Less),
+            factory.skip(" "),
+            factory.make("<=", LessEqual),
+            factory.skip(" "),
+            factory.make(">", Greater),
+            factory.skip(" "),
+            factory.make(">=", GreaterEqual),
+            factory.skip(" "),
+            factory.make("&&", DoubleAmpersand),
+            factory.skip(" "),
+            factory.make("||", DoublePipe),
+            factory.skip(" "),
+            factory.make("%", Percent),
+            factory.skip(" "),
+            factory.make("%=", PercentEqual),
+            factory.skip(" "),
+            factory.make("**", DoubleAsterisk),
+            factory.skip(" "),
+            factory.make("&", Ampersand),
+            factory.skip(" "),
+            factory.make("|", Pipe),
+            factory.skip(" "),
+            factory.make("^", Caret),
+            factory.skip(" "),
+            factory.make("<<", DoubleLess),
+            factory.skip(" "),
+            factory.make(">>", DoubleGreater),
+            factory.skip(" "),
+            factory.make("[", LBracket),
+            factory.skip(" "),
+            factory.make("]", RBracket),
+            factory.skip(" "),
+            factory.make(":", Colon),
+            factory.skip(" "),
+            factory.make(".", Dot),
+            factory.skip(" "),
+            factory.make("::", DoubleColon),
+            factory.skip(" "),
+            factory.make("=>", EqualGreater),
+            factory.skip(" "),
+            factory.make("->", MinusGreater),
+            factory.skip(" "),
             factory.make("Å", Error),
         ]
         .into_iter()
-        .filter_map(|option| option)
+        .flatten()
         .collect();
 
         assert_eq!(lexer.into_iter().collect::<Vec<Token>>(), tokens);
@@ -559,7 +740,29 @@ let b = function_name();"#,
             factory.make(";", TokenVariant::Semicolon),
         ]
         .into_iter()
-        .filter_map(|option| option)
+        .flatten()
+        .collect();
+
+        assert_eq!(lexer.into_iter().collect::<Vec<Token>>(), tokens);
+    }
+
+    #[test]
+    fn should_tokenize_strings() {
+        let input = String::from(r#""hello" "\"\\" "\u{1F600}" "unterminated \""#);
+        let lexer = Lexer::new(input.chars());
+        let mut factory = TokenFactory::new();
+
+        let tokens: Vec<Token> = vec![
+            factory.make(r#""hello""#, TokenVariant::String),
+            factory.skip(" "),
+            factory.make(r#""\"\\""#, TokenVariant::String),
+            factory.skip(" "),
+            factory.make(r#""\u{1F600}""#, TokenVariant::String),
+            factory.skip(" "),
+            factory.make(r#""unterminated \""#, TokenVariant::UnterminatedString),
+        ]
+        .into_iter()
+        .flatten()
         .collect();
 
         assert_eq!(lexer.into_iter().collect::<Vec<Token>>(), tokens);
@@ -6,6 +6,17 @@ pub enum TokenVariant {
     MutKeyword,
     FnKeyword,
     ReturnKeyword,
+    WhileKeyword,
+    BreakKeyword,
+    ContinueKeyword,
+    IfKeyword,
+    ElseKeyword,
+    TrueKeyword,
+    FalseKeyword,
+    StructKeyword,
+    ImplKeyword,
+    EnumKeyword,
+    MatchKeyword,
     Identifier,
     LParenthesis,
     RParenthesis,
@@ -21,12 +32,36 @@ pub enum TokenVariant {
     SlashEqual,
     Slash,
     Semicolon,
+    Comma,
     Integer,
     Float,
+    String,
+    UnterminatedString,
     Error,
     Exclamation,
     ExclamationEqual,
     DoubleEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    DoubleAmpersand,
+    DoublePipe,
+    Percent,
+    PercentEqual,
+    DoubleAsterisk,
+    Ampersand,
+    Pipe,
+    Caret,
+    DoubleLess,
+    DoubleGreater,
+    LBracket,
+    RBracket,
+    Colon,
+    Dot,
+    DoubleColon,
+    EqualGreater,
+    MinusGreater,
 }
 
 impl Display for TokenVariant {
@@ -36,6 +71,17 @@ impl Display for TokenVariant {
             TokenVariant::MutKeyword => "MutKeyword",
             TokenVariant::FnKeyword => "FnKeyword",
             TokenVariant::ReturnKeyword => "ReturnKeyword",
+            TokenVariant::WhileKeyword => "WhileKeyword",
+            TokenVariant::BreakKeyword => "BreakKeyword",
+            TokenVariant::ContinueKeyword => "ContinueKeyword",
+            TokenVariant::IfKeyword => "IfKeyword",
+            TokenVariant::ElseKeyword => "ElseKeyword",
+            TokenVariant::TrueKeyword => "TrueKeyword",
+            TokenVariant::FalseKeyword => "FalseKeyword",
+            TokenVariant::StructKeyword => "StructKeyword",
+            TokenVariant::ImplKeyword => "ImplKeyword",
+            TokenVariant::EnumKeyword => "EnumKeyword",
+            TokenVariant::MatchKeyword => "MatchKeyword",
             TokenVariant::Identifier => "Identifier",
             TokenVariant::LParenthesis => "LeftParenthesis",
             TokenVariant::RParenthesis => "RightParenthesis",
@@ -51,12 +97,36 @@ impl Display for TokenVariant {
             TokenVariant::SlashEqual => "SlashEqual",
             TokenVariant::Slash => "Slash",
             TokenVariant::Semicolon => "Semicolon",
+            TokenVariant::Comma => "Comma",
             TokenVariant::Integer => "Integer",
             TokenVariant::Float => "Float",
+            TokenVariant::String => "String",
+            TokenVariant::UnterminatedString => "UnterminatedString",
             TokenVariant::Error => "Error",
             TokenVariant::Exclamation => "Exclamation",
             TokenVariant::ExclamationEqual => "ExclamationEqual",
             TokenVariant::DoubleEqual => "DoubleEqual",
+            TokenVariant::Less => "Less",
+            TokenVariant::LessEqual => "LessEqual",
+            TokenVariant::Greater => "Greater",
+            TokenVariant::GreaterEqual => "GreaterEqual",
+            TokenVariant::DoubleAmpersand => "DoubleAmpersand",
+            TokenVariant::DoublePipe => "DoublePipe",
+            TokenVariant::Percent => "Percent",
+            TokenVariant::PercentEqual => "PercentEqual",
+            TokenVariant::DoubleAsterisk => "DoubleAsterisk",
+            TokenVariant::Ampersand => "Ampersand",
+            TokenVariant::Pipe => "Pipe",
+            TokenVariant::Caret => "Caret",
+            TokenVariant::DoubleLess => "DoubleLess",
+            TokenVariant::DoubleGreater => "DoubleGreater",
+            TokenVariant::LBracket => "LeftBracket",
+            TokenVariant::RBracket => "RightBracket",
+            TokenVariant::Colon => "Colon",
+            TokenVariant::Dot => "Dot",
+            TokenVariant::DoubleColon => "DoubleColon",
+            TokenVariant::EqualGreater => "EqualGreater",
+            TokenVariant::MinusGreater => "MinusGreater",
         })
     }
 }
@@ -14,14 +14,14 @@ macro_rules! try_peek_or_error {
     }};
     (parser: $self:ident, error: $error_type:ident::Error) => {{
         let Some(next) = $self.iter.peek() else {
-                                let message = format!("expected token, got end of file");
-                                let position = Position {
-                                        index: $self.text.len(),
-                                        line: $self.text.split('\n').count(),
-                                        column: $self.text.split('\n').last().unwrap_or("").len(),
-                                    };
-                                    return Self::node($error_type::Error(message), position);
-                                };
+            let message = format!("expected token, got end of file");
+            let position = Position {
+                index: $self.text.len(),
+                line: $self.text.split('\n').count(),
+                column: $self.text.split('\n').last().unwrap_or("").len(),
+            };
+            return Self::node($error_type::Error(message), position);
+        };
         next
     }};
 }
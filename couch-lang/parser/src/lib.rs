@@ -4,14 +4,39 @@ use couch_lang_lexer::{Token, TokenVariant};
 
 mod error_helper;
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub enum Statement {
+    Function {
+        identifier: Box<Node<Expression>>,
+        parameters: Vec<Node<Parameter>>,
+        return_type: Option<Box<Node<TypeAnnotation>>>,
+        body: Box<Node<Expression>>,
+    },
     Let {
         mutable: bool,
         identifier: Box<Node<Expression>>,
+        annotation: Option<Box<Node<TypeAnnotation>>>,
         value: Box<Node<Expression>>,
     },
     Return(Option<Box<Node<Expression>>>),
+    While {
+        condition: Box<Node<Expression>>,
+        body: Box<Node<Expression>>,
+    },
+    Break,
+    Continue,
+    Struct {
+        identifier: Box<Node<Expression>>,
+        fields: Vec<Node<Expression>>,
+    },
+    Impl {
+        identifier: Box<Node<Expression>>,
+        methods: Vec<Node<Statement>>,
+    },
+    Enum {
+        identifier: Box<Node<Expression>>,
+        variants: Vec<(Node<Expression>, Vec<Node<Expression>>)>,
+    },
     Assignment {
         left: Box<Node<Expression>>,
         right: Box<Node<Expression>>,
@@ -21,10 +46,24 @@ pub enum Statement {
     Error(String),
 }
 
+#[derive(Debug, PartialEq, Clone)]
 pub enum Parameter {
     Item {
         mutable: bool,
         identifier: Box<Node<Expression>>,
+        annotation: Option<Box<Node<TypeAnnotation>>>,
+    },
+    Error(String),
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum TypeAnnotation {
+    Named(String),
+    List(Box<Node<TypeAnnotation>>),
+    Map(Box<Node<TypeAnnotation>>),
+    Function {
+        parameters: Vec<Node<TypeAnnotation>>,
+        return_type: Box<Node<TypeAnnotation>>,
     },
     Error(String),
 }
@@ -33,11 +72,46 @@ pub enum Parameter {
 pub enum Expression {
     Integer(i64),
     Float(f64),
+    Bool(bool),
+    String(String),
     Call {
         subject: Box<Node<Expression>>,
         arguments: Vec<Node<Expression>>,
     },
     Identifier(String),
+    List(Vec<Node<Expression>>),
+    Index {
+        subject: Box<Node<Expression>>,
+        index: Box<Node<Expression>>,
+    },
+    Map(Vec<(Node<Expression>, Node<Expression>)>),
+    Struct {
+        identifier: Box<Node<Expression>>,
+        fields: Vec<(Node<Expression>, Node<Expression>)>,
+    },
+    Member {
+        subject: Box<Node<Expression>>,
+        member: Box<Node<Expression>>,
+    },
+    Path {
+        subject: Box<Node<Expression>>,
+        member: Box<Node<Expression>>,
+    },
+    Match {
+        subject: Box<Node<Expression>>,
+        arms: Vec<MatchArm>,
+    },
+    Function {
+        parameters: Vec<Node<Parameter>>,
+        return_type: Option<Box<Node<TypeAnnotation>>>,
+        body: Box<Node<Expression>>,
+    },
+    Block(Vec<Node<Statement>>),
+    If {
+        condition: Box<Node<Expression>>,
+        truthy: Box<Node<Expression>>,
+        falsy: Option<Box<Node<Expression>>>,
+    },
     Unary {
         subject: Box<Node<Expression>>,
         variant: UnaryVariant,
@@ -50,6 +124,26 @@ pub enum Expression {
     Error(String),
 }
 
+#[derive(Debug, PartialEq, Clone)]
+pub struct MatchArm {
+    pub pattern: Node<Pattern>,
+    pub guard: Option<Node<Expression>>,
+    pub body: Node<Expression>,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Pattern {
+    Wildcard,
+    Identifier(String),
+    Literal(Expression),
+    Variant {
+        enumeration: Box<Node<Expression>>,
+        variant: Box<Node<Expression>>,
+        fields: Vec<Node<Pattern>>,
+    },
+    Error(String),
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum UnaryVariant {
     NegateNumber,
@@ -64,8 +158,50 @@ pub enum BinaryVariant {
     Division,
     Equal,
     NotEqual,
+    Less,
+    LessEqual,
+    Greater,
+    GreaterEqual,
+    And,
+    Or,
+    Modulo,
+    Exponentiation,
+    BitwiseAnd,
+    BitwiseOr,
+    BitwiseXor,
+    LeftShift,
+    RightShift,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Associativity {
+    Left,
+    Right,
 }
 
+#[rustfmt::skip]
+pub const BINARY_OPERATORS: &[(TokenVariant, BinaryVariant, u8, Associativity)] = &[
+    (TokenVariant::DoublePipe,       BinaryVariant::Or,             1,  Associativity::Left),
+    (TokenVariant::DoubleAmpersand,  BinaryVariant::And,            2,  Associativity::Left),
+    (TokenVariant::DoubleEqual,      BinaryVariant::Equal,          3,  Associativity::Left),
+    (TokenVariant::ExclamationEqual, BinaryVariant::NotEqual,       3,  Associativity::Left),
+    (TokenVariant::Less,             BinaryVariant::Less,           4,  Associativity::Left),
+    (TokenVariant::LessEqual,        BinaryVariant::LessEqual,      4,  Associativity::Left),
+    (TokenVariant::Greater,          BinaryVariant::Greater,        4,  Associativity::Left),
+    (TokenVariant::GreaterEqual,     BinaryVariant::GreaterEqual,   4,  Associativity::Left),
+    (TokenVariant::Pipe,             BinaryVariant::BitwiseOr,      5,  Associativity::Left),
+    (TokenVariant::Caret,            BinaryVariant::BitwiseXor,     6,  Associativity::Left),
+    (TokenVariant::Ampersand,        BinaryVariant::BitwiseAnd,     7,  Associativity::Left),
+    (TokenVariant::DoubleLess,       BinaryVariant::LeftShift,      8,  Associativity::Left),
+    (TokenVariant::DoubleGreater,    BinaryVariant::RightShift,     8,  Associativity::Left),
+    (TokenVariant::Plus,             BinaryVariant::Addition,       9,  Associativity::Left),
+    (TokenVariant::Minus,            BinaryVariant::Subtraction,    9,  Associativity::Left),
+    (TokenVariant::Asterisk,         BinaryVariant::Multiplication, 10, Associativity::Left),
+    (TokenVariant::Slash,            BinaryVariant::Division,       10, Associativity::Left),
+    (TokenVariant::Percent,          BinaryVariant::Modulo,         10, Associativity::Left),
+    (TokenVariant::DoubleAsterisk,   BinaryVariant::Exponentiation, 11, Associativity::Right),
+];
+
 #[derive(Debug, PartialEq, Clone)]
 pub enum AssignmentVariant {
     Base,
@@ -73,6 +209,7 @@ pub enum AssignmentVariant {
     Subtraction,
     Multiplication,
     Division,
+    Modulo,
 }
 
 #[derive(Debug, PartialEq, Clone)]
@@ -98,12 +235,25 @@ impl From<&Token> for Position {
     }
 }
 
+pub const MAX_NESTING_DEPTH: usize = 256;
+const STACK_RED_ZONE: usize = 128 * 1024;
+const STACK_SEGMENT: usize = 1024 * 1024;
+
+fn is_block_like(expression: &Expression) -> bool {
+    matches!(
+        expression,
+        Expression::If { .. } | Expression::Block(_) | Expression::Match { .. }
+    )
+}
+
 pub struct Parser<I>
 where
     I: Iterator<Item = Token>,
 {
     iter: Peekable<I>,
     text: String,
+    struct_literals: bool,
+    depth: usize,
 }
 
 impl<I> Parser<I>
@@ -114,6 +264,8 @@ where
         Self {
             iter: iter.peekable(),
             text,
+            struct_literals: true,
+            depth: 0,
         }
     }
     pub fn parse_statements(&mut self) -> Vec<Node<Statement>> {
@@ -124,14 +276,196 @@ where
         result
     }
     pub fn parse_statement(&mut self) -> Node<Statement> {
+        self.nested(Statement::Error, false, Self::parse_statement_body)
+    }
+    fn parse_statement_body(&mut self) -> Node<Statement> {
         let keyword = try_peek_or_error!(parser: self, error: Statement::Error);
-        let statement = match &keyword.variant {
+        match &keyword.variant {
             TokenVariant::ReturnKeyword => self.parse_return(),
             TokenVariant::LetKeyword => self.parse_let(),
-            TokenVariant::FnKeyword => todo!("parse fn"),
+            TokenVariant::FnKeyword => self.parse_function(),
+            TokenVariant::WhileKeyword => self.parse_while(),
+            TokenVariant::BreakKeyword => self.parse_break_or_continue(),
+            TokenVariant::ContinueKeyword => self.parse_break_or_continue(),
+            TokenVariant::StructKeyword => self.parse_struct(),
+            TokenVariant::ImplKeyword => self.parse_impl(),
+            TokenVariant::EnumKeyword => self.parse_enum(),
             _ => self.parse_assignment(),
+        }
+    }
+    pub fn parse_function(&mut self) -> Node<Statement> {
+        let keyword = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            keyword.variant,
+            TokenVariant::FnKeyword,
+            "called out of order"
+        );
+        let position: Position = (&keyword).into();
+
+        let next = try_peek_or_error!(parser: self, expect: Identifier, error: Statement::Error);
+        if next.variant == TokenVariant::LParenthesis {
+            let function = self.parse_function_literal(position.clone());
+            if let Some(error) = self.expression_error(&function) {
+                return error;
+            }
+            return Self::node(Statement::Expression(function), position);
+        }
+        assert_equal_variant!(next == Identifier, error: Statement::Error);
+        let identifier = self.parse_operand();
+
+        let function = self.parse_function_literal(position.clone());
+        match function.value {
+            Expression::Function {
+                parameters,
+                return_type,
+                body,
+            } => Self::node(
+                Statement::Function {
+                    identifier: Box::new(identifier),
+                    parameters,
+                    return_type,
+                    body,
+                },
+                position,
+            ),
+            Expression::Error(message) => Self::node(Statement::Error(message), function.position),
+            value => panic!("expected Function, got {value:#?}"),
+        }
+    }
+    fn parse_function_literal(&mut self, position: Position) -> Node<Expression> {
+        let next = try_peek_or_error!(parser: self, expect: LParenthesis, error: Expression::Error);
+        assert_equal_variant!(next == LParenthesis, error: Expression::Error);
+        self.iter.next().expect("already peeked");
+
+        let mut parameters = Vec::new();
+        loop {
+            let next =
+                try_peek_or_error!(parser: self, expect: RParenthesis, error: Expression::Error);
+            if next.variant == TokenVariant::RParenthesis {
+                self.iter.next().expect("already peeked");
+                break;
+            }
+            match self.parse_parameter() {
+                Node {
+                    value: Parameter::Error(message),
+                    position,
+                } => return Self::node(Expression::Error(message), position),
+                parameter => parameters.push(parameter),
+            }
+            let next =
+                try_peek_or_error!(parser: self, expect: RParenthesis, error: Expression::Error);
+            match next.variant {
+                TokenVariant::Comma => {
+                    self.iter.next().expect("already peeked");
+                }
+                _ => assert_equal_variant!(next == RParenthesis, error: Expression::Error),
+            }
+        }
+
+        let next = try_peek_or_error!(parser: self, expect: LBrace, error: Expression::Error);
+        let return_type = match next.variant {
+            TokenVariant::MinusGreater => {
+                self.iter.next().expect("already peeked");
+                match self.parse_type_annotation() {
+                    Node {
+                        value: TypeAnnotation::Error(message),
+                        position,
+                    } => return Self::node(Expression::Error(message), position),
+                    annotation => Some(Box::new(annotation)),
+                }
+            }
+            _ => None,
+        };
+
+        let next = try_peek_or_error!(parser: self, expect: LBrace, error: Expression::Error);
+        assert_equal_variant!(next == LBrace, error: Expression::Error);
+        let body = self.parse_block();
+        if let Expression::Error(_) = body.value {
+            return body;
+        }
+
+        Self::node(
+            Expression::Function {
+                parameters,
+                return_type,
+                body: Box::new(body),
+            },
+            position,
+        )
+    }
+    pub fn parse_block(&mut self) -> Node<Expression> {
+        let brace = self.iter.next().expect("called out of order");
+        debug_assert_eq!(brace.variant, TokenVariant::LBrace, "called out of order");
+        let position: Position = (&brace).into();
+        self.parse_block_rest(Vec::new(), position)
+    }
+    fn parse_block_rest(
+        &mut self,
+        statements: Vec<Node<Statement>>,
+        position: Position,
+    ) -> Node<Expression> {
+        self.with_struct_literals(true, |parser| {
+            parser.parse_block_statements(statements, position)
+        })
+    }
+    fn parse_block_statements(
+        &mut self,
+        mut statements: Vec<Node<Statement>>,
+        position: Position,
+    ) -> Node<Expression> {
+        loop {
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Expression::Error);
+            if next.variant == TokenVariant::RBrace {
+                self.iter.next().expect("already peeked");
+                break;
+            }
+            statements.push(self.parse_statement());
+        }
+        Self::node(Expression::Block(statements), position)
+    }
+    pub fn parse_if(&mut self) -> Node<Expression> {
+        let keyword = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            keyword.variant,
+            TokenVariant::IfKeyword,
+            "called out of order"
+        );
+        let position: Position = (&keyword).into();
+
+        try_peek_or_error!(parser: self, error: Expression::Error);
+        let condition = self.with_struct_literals(false, Self::parse_expression);
+
+        let next = try_peek_or_error!(parser: self, expect: LBrace, error: Expression::Error);
+        assert_equal_variant!(next == LBrace, error: Expression::Error);
+        let truthy = self.parse_block();
+
+        let falsy = match self.iter.peek() {
+            Some(Token {
+                variant: TokenVariant::ElseKeyword,
+                ..
+            }) => {
+                self.iter.next().expect("already peeked");
+                let next =
+                    try_peek_or_error!(parser: self, expect: LBrace, error: Expression::Error);
+                match next.variant {
+                    TokenVariant::IfKeyword => Some(Box::new(self.parse_if())),
+                    _ => {
+                        assert_equal_variant!(next == LBrace, error: Expression::Error);
+                        Some(Box::new(self.parse_block()))
+                    }
+                }
+            }
+            _ => None,
         };
-        statement
+
+        Self::node(
+            Expression::If {
+                condition: Box::new(condition),
+                truthy: Box::new(truthy),
+                falsy,
+            },
+            position,
+        )
     }
     pub fn parse_return(&mut self) -> Node<Statement> {
         let keyword = self.iter.next().expect("called out of order");
@@ -149,10 +483,11 @@ where
                 node
             }
             _ => {
-                let node = Self::node(
-                    Statement::Return(Some(Box::new(self.parse_expression()))),
-                    position,
-                );
+                let value = self.parse_expression();
+                if let Some(error) = self.expression_error(&value) {
+                    return error;
+                }
+                let node = Self::node(Statement::Return(Some(Box::new(value))), position);
                 let token =
                     try_peek_or_error!(parser: self, expect: Semicolon, error: Statement::Error);
                 assert_equal_variant!(token == Semicolon, error: Statement::Error);
@@ -161,6 +496,206 @@ where
             }
         }
     }
+    pub fn parse_while(&mut self) -> Node<Statement> {
+        let keyword = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            keyword.variant,
+            TokenVariant::WhileKeyword,
+            "called out of order"
+        );
+        let position: Position = (&keyword).into();
+
+        try_peek_or_error!(parser: self, error: Statement::Error);
+        let condition = self.with_struct_literals(false, Self::parse_expression);
+
+        let next = try_peek_or_error!(parser: self, expect: LBrace, error: Statement::Error);
+        assert_equal_variant!(next == LBrace, error: Statement::Error);
+        let body = self.parse_block();
+        if let Node {
+            value: Expression::Error(message),
+            position,
+        } = body
+        {
+            return Self::node(Statement::Error(message), position);
+        }
+
+        Self::node(
+            Statement::While {
+                condition: Box::new(condition),
+                body: Box::new(body),
+            },
+            position,
+        )
+    }
+    pub fn parse_break_or_continue(&mut self) -> Node<Statement> {
+        let keyword = self.iter.next().expect("called out of order");
+        let statement = match keyword.variant {
+            TokenVariant::BreakKeyword => Statement::Break,
+            TokenVariant::ContinueKeyword => Statement::Continue,
+            _ => unreachable!("called out of order"),
+        };
+        let position: Position = (&keyword).into();
+
+        let next = try_peek_or_error!(parser: self, expect: Semicolon, error: Statement::Error);
+        assert_equal_variant!(next == Semicolon, error: Statement::Error);
+        self.iter.next().expect("already peeked");
+
+        Self::node(statement, position)
+    }
+    pub fn parse_struct(&mut self) -> Node<Statement> {
+        let keyword = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            keyword.variant,
+            TokenVariant::StructKeyword,
+            "called out of order"
+        );
+        let position: Position = (&keyword).into();
+
+        let next = try_peek_or_error!(parser: self, expect: Identifier, error: Statement::Error);
+        assert_equal_variant!(next == Identifier, error: Statement::Error);
+        let identifier = self.parse_identifier();
+
+        let next = try_peek_or_error!(parser: self, expect: LBrace, error: Statement::Error);
+        assert_equal_variant!(next == LBrace, error: Statement::Error);
+        self.iter.next().expect("already peeked");
+
+        let mut fields = Vec::new();
+        loop {
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Statement::Error);
+            if next.variant == TokenVariant::RBrace {
+                self.iter.next().expect("already peeked");
+                break;
+            }
+            assert_equal_variant!(next == Identifier, error: Statement::Error);
+            fields.push(self.parse_identifier());
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Statement::Error);
+            match next.variant {
+                TokenVariant::Comma => {
+                    self.iter.next().expect("already peeked");
+                }
+                _ => assert_equal_variant!(next == RBrace, error: Statement::Error),
+            }
+        }
+
+        Self::node(
+            Statement::Struct {
+                identifier: Box::new(identifier),
+                fields,
+            },
+            position,
+        )
+    }
+    pub fn parse_impl(&mut self) -> Node<Statement> {
+        let keyword = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            keyword.variant,
+            TokenVariant::ImplKeyword,
+            "called out of order"
+        );
+        let position: Position = (&keyword).into();
+
+        let next = try_peek_or_error!(parser: self, expect: Identifier, error: Statement::Error);
+        assert_equal_variant!(next == Identifier, error: Statement::Error);
+        let identifier = self.parse_identifier();
+
+        let next = try_peek_or_error!(parser: self, expect: LBrace, error: Statement::Error);
+        assert_equal_variant!(next == LBrace, error: Statement::Error);
+        self.iter.next().expect("already peeked");
+
+        let mut methods = Vec::new();
+        loop {
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Statement::Error);
+            if next.variant == TokenVariant::RBrace {
+                self.iter.next().expect("already peeked");
+                break;
+            }
+            assert_equal_variant!(next == FnKeyword, error: Statement::Error);
+            let method = self.parse_function();
+            match method.value {
+                Statement::Function { .. } => methods.push(method),
+                Statement::Error(_) => return method,
+                _ => {
+                    let message = "expected method name".to_string();
+                    return Self::node(Statement::Error(message), method.position);
+                }
+            }
+        }
+
+        Self::node(
+            Statement::Impl {
+                identifier: Box::new(identifier),
+                methods,
+            },
+            position,
+        )
+    }
+    pub fn parse_enum(&mut self) -> Node<Statement> {
+        let keyword = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            keyword.variant,
+            TokenVariant::EnumKeyword,
+            "called out of order"
+        );
+        let position: Position = (&keyword).into();
+
+        let next = try_peek_or_error!(parser: self, expect: Identifier, error: Statement::Error);
+        assert_equal_variant!(next == Identifier, error: Statement::Error);
+        let identifier = self.parse_identifier();
+
+        let next = try_peek_or_error!(parser: self, expect: LBrace, error: Statement::Error);
+        assert_equal_variant!(next == LBrace, error: Statement::Error);
+        self.iter.next().expect("already peeked");
+
+        let mut variants = Vec::new();
+        loop {
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Statement::Error);
+            if next.variant == TokenVariant::RBrace {
+                self.iter.next().expect("already peeked");
+                break;
+            }
+            assert_equal_variant!(next == Identifier, error: Statement::Error);
+            let variant = self.parse_identifier();
+
+            let mut fields = Vec::new();
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Statement::Error);
+            if next.variant == TokenVariant::LParenthesis {
+                self.iter.next().expect("already peeked");
+                loop {
+                    let next = try_peek_or_error!(parser: self, expect: RParenthesis, error: Statement::Error);
+                    if next.variant == TokenVariant::RParenthesis {
+                        self.iter.next().expect("already peeked");
+                        break;
+                    }
+                    assert_equal_variant!(next == Identifier, error: Statement::Error);
+                    fields.push(self.parse_identifier());
+                    let next = try_peek_or_error!(parser: self, expect: RParenthesis, error: Statement::Error);
+                    match next.variant {
+                        TokenVariant::Comma => {
+                            self.iter.next().expect("already peeked");
+                        }
+                        _ => assert_equal_variant!(next == RParenthesis, error: Statement::Error),
+                    }
+                }
+            }
+            variants.push((variant, fields));
+
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Statement::Error);
+            match next.variant {
+                TokenVariant::Comma => {
+                    self.iter.next().expect("already peeked");
+                }
+                _ => assert_equal_variant!(next == RBrace, error: Statement::Error),
+            }
+        }
+
+        Self::node(
+            Statement::Enum {
+                identifier: Box::new(identifier),
+                variants,
+            },
+            position,
+        )
+    }
     pub fn parse_let(&mut self) -> Node<Statement> {
         let keyword = self.iter.peek().expect("called out of order");
         debug_assert_eq!(
@@ -177,15 +712,16 @@ where
 
         self.iter.next();
 
-        let (mutable, identifier) = match self.parse_parameter() {
+        let (mutable, identifier, annotation) = match self.parse_parameter() {
             Node {
                 value:
                     Parameter::Item {
                         mutable,
                         identifier,
+                        annotation,
                     },
                 ..
-            } => (mutable, identifier),
+            } => (mutable, identifier, annotation),
             Node {
                 value: Parameter::Error(message),
                 position,
@@ -198,6 +734,9 @@ where
 
         try_peek_or_error!(parser: self, error: Statement::Error);
         let value = self.parse_expression();
+        if let Some(error) = self.expression_error(&value) {
+            return error;
+        }
 
         let next = try_peek_or_error!(parser: self, expect: Semicolon, error: Statement::Error);
         assert_equal_variant!(next == Semicolon, error: Statement::Error);
@@ -207,6 +746,7 @@ where
             Statement::Let {
                 mutable,
                 identifier,
+                annotation,
                 value: Box::new(value),
             },
             position,
@@ -224,28 +764,159 @@ where
         if mutable {
             self.iter.next();
         }
-        try_peek_or_error!(parser: self, expect: Identifier, error: Parameter::Error);
+        let next = try_peek_or_error!(parser: self, expect: Identifier, error: Parameter::Error);
+        assert_equal_variant!(next == Identifier, error: Parameter::Error);
         let identifier = self.parse_operand();
 
+        let annotation = match self.iter.peek() {
+            Some(Token {
+                variant: TokenVariant::Colon,
+                ..
+            }) => {
+                self.iter.next().expect("already peeked");
+                match self.parse_type_annotation() {
+                    Node {
+                        value: TypeAnnotation::Error(message),
+                        position,
+                    } => return Self::node(Parameter::Error(message), position),
+                    annotation => Some(Box::new(annotation)),
+                }
+            }
+            _ => None,
+        };
+
         Self::node(
             Parameter::Item {
                 mutable,
                 identifier: Box::new(identifier),
+                annotation,
             },
             position,
         )
     }
+    pub fn parse_type_annotation(&mut self) -> Node<TypeAnnotation> {
+        self.nested(
+            TypeAnnotation::Error,
+            true,
+            Self::parse_type_annotation_body,
+        )
+    }
+    fn parse_type_annotation_body(&mut self) -> Node<TypeAnnotation> {
+        let token = try_peek_or_error!(parser: self, error: TypeAnnotation::Error);
+        let position: Position = token.into();
+        match token.variant {
+            TokenVariant::Identifier => {
+                let token = self.iter.next().expect("already peeked");
+                let name = self.text[token.index..token.index + token.length].to_owned();
+                Self::node(TypeAnnotation::Named(name), position)
+            }
+            TokenVariant::LBracket | TokenVariant::LBrace => {
+                let opening = self.iter.next().expect("already peeked");
+                let element = self.parse_type_annotation();
+                if let TypeAnnotation::Error(_) = element.value {
+                    return element;
+                }
+                let next = try_peek_or_error!(parser: self, error: TypeAnnotation::Error);
+                let element = Box::new(element);
+                if opening.variant == TokenVariant::LBracket {
+                    assert_equal_variant!(next == RBracket, error: TypeAnnotation::Error);
+                    self.iter.next().expect("already peeked");
+                    Self::node(TypeAnnotation::List(element), position)
+                } else {
+                    assert_equal_variant!(next == RBrace, error: TypeAnnotation::Error);
+                    self.iter.next().expect("already peeked");
+                    Self::node(TypeAnnotation::Map(element), position)
+                }
+            }
+            TokenVariant::FnKeyword => {
+                self.iter.next().expect("already peeked");
+                let next = try_peek_or_error!(parser: self, expect: LParenthesis, error: TypeAnnotation::Error);
+                assert_equal_variant!(next == LParenthesis, error: TypeAnnotation::Error);
+                self.iter.next().expect("already peeked");
+
+                let mut parameters = Vec::new();
+                loop {
+                    let next = try_peek_or_error!(parser: self, expect: RParenthesis, error: TypeAnnotation::Error);
+                    if next.variant == TokenVariant::RParenthesis {
+                        self.iter.next().expect("already peeked");
+                        break;
+                    }
+                    let parameter = self.parse_type_annotation();
+                    if let TypeAnnotation::Error(_) = parameter.value {
+                        return parameter;
+                    }
+                    parameters.push(parameter);
+                    let next = try_peek_or_error!(parser: self, expect: RParenthesis, error: TypeAnnotation::Error);
+                    match next.variant {
+                        TokenVariant::Comma => {
+                            self.iter.next().expect("already peeked");
+                        }
+                        _ => {
+                            assert_equal_variant!(next == RParenthesis, error: TypeAnnotation::Error)
+                        }
+                    }
+                }
+
+                let return_type = match self.iter.peek() {
+                    Some(Token {
+                        variant: TokenVariant::MinusGreater,
+                        ..
+                    }) => {
+                        self.iter.next().expect("already peeked");
+                        let return_type = self.parse_type_annotation();
+                        if let TypeAnnotation::Error(_) = return_type.value {
+                            return return_type;
+                        }
+                        return_type
+                    }
+                    _ => Self::node(TypeAnnotation::Named("unit".to_string()), position.clone()),
+                };
+                Self::node(
+                    TypeAnnotation::Function {
+                        parameters,
+                        return_type: Box::new(return_type),
+                    },
+                    position,
+                )
+            }
+            ref variant => {
+                let message = format!("unexpected type {variant:#?}");
+                self.iter.next().expect("already peeked");
+                Self::node(TypeAnnotation::Error(message), position)
+            }
+        }
+    }
     pub fn parse_assignment(&mut self) -> Node<Statement> {
-        let left = self.parse_expression();
+        let left = match self.iter.peek().map(|token| &token.variant) {
+            Some(TokenVariant::IfKeyword | TokenVariant::MatchKeyword | TokenVariant::LBrace) => {
+                // like in Rust, a block-like expression ends the statement
+                let operand = self.parse_operand();
+                if is_block_like(&operand.value) {
+                    operand
+                } else {
+                    let subject = self.parse_member_index_call_from(operand);
+                    self.parse_binary_from(subject, 0)
+                }
+            }
+            _ => self.parse_expression(),
+        };
+        self.parse_assignment_from(left)
+    }
+    fn parse_assignment_from(&mut self, left: Node<Expression>) -> Node<Statement> {
+        if let Some(error) = self.expression_error(&left) {
+            return error;
+        }
+        let block_like = is_block_like(&left.value);
         let Some(operand) = self.iter.peek() else {
-            let position = Position { ..left.position };
+            if block_like {
+                let position = left.position.clone();
+                return Self::node(Statement::Expression(left), position);
+            }
+            let position = left.position.clone();
             let semicolon = try_peek_or_error!(parser: self, error: Statement::Error);
             assert_equal_variant!(semicolon == Semicolon, error: Statement::Error);
             self.iter.next().expect("already peeked");
-            return Self::node(
-                Statement::Expression(left),
-                position,
-            );
+            return Self::node(Statement::Expression(left), position);
         };
 
         let variant = match operand.variant {
@@ -254,17 +925,25 @@ where
             TokenVariant::MinusEqual => AssignmentVariant::Subtraction,
             TokenVariant::PlusEqual => AssignmentVariant::Addition,
             TokenVariant::SlashEqual => AssignmentVariant::Division,
-            _ => {
-                let position = Position { ..left.position };
+            TokenVariant::PercentEqual => AssignmentVariant::Modulo,
+            _ if block_like && operand.variant != TokenVariant::Semicolon => {
+                let position = left.position.clone();
+                return Self::node(Statement::Expression(left), position);
+            }
+            _ => {
+                let position = left.position.clone();
                 let semicolon = try_peek_or_error!(parser: self, error: Statement::Error);
                 assert_equal_variant!(semicolon == Semicolon, error: Statement::Error);
                 self.iter.next().expect("already peeked");
                 return Self::node(Statement::Expression(left), position);
             }
         };
-        let position = Position { ..left.position };
+        let position = left.position.clone();
         self.iter.next().expect("already peeked");
         let right = self.parse_expression();
+        if let Some(error) = self.expression_error(&right) {
+            return error;
+        }
         let semicolon = try_peek_or_error!(parser: self, error: Statement::Error);
         assert_equal_variant!(semicolon == Semicolon, error: Statement::Error);
         self.iter.next().expect("already peeked");
@@ -279,107 +958,549 @@ where
     }
 
     pub fn parse_expression(&mut self) -> Node<Expression> {
-        self.parse_equality()
+        self.parse_binary(0)
     }
 
-    pub fn parse_equality(&mut self) -> Node<Expression> {
-        let left = self.parse_add_subtract();
-        let Some(operand) = self.iter.peek() else {
-            return left;
-        };
-        let variant = match operand.variant {
-            TokenVariant::DoubleEqual => BinaryVariant::Equal,
-            TokenVariant::ExclamationEqual => BinaryVariant::NotEqual,
-            _ => return left,
+    fn parse_binary(&mut self, min_binding_power: u8) -> Node<Expression> {
+        let left = self.parse_unary();
+        self.parse_binary_from(left, min_binding_power)
+    }
+    fn parse_binary_from(
+        &mut self,
+        mut left: Node<Expression>,
+        min_binding_power: u8,
+    ) -> Node<Expression> {
+        loop {
+            let Some(token) = self.iter.peek() else {
+                break left;
+            };
+            let Some((_, variant, binding_power, associativity)) = BINARY_OPERATORS
+                .iter()
+                .find(|(operator, ..)| *operator == token.variant)
+            else {
+                break left;
+            };
+            if *binding_power < min_binding_power {
+                break left;
+            }
+            self.iter.next().expect("already peeked");
+            let right = match associativity {
+                Associativity::Left => self.parse_binary(binding_power + 1),
+                Associativity::Right => self.parse_binary(*binding_power),
+            };
+            let position = left.position.clone();
+            left = Self::node(
+                Expression::Binary {
+                    left: Box::new(left),
+                    right: Box::new(right),
+                    variant: variant.clone(),
+                },
+                position,
+            );
+        }
+    }
+    fn parse_unary(&mut self) -> Node<Expression> {
+        self.nested(Expression::Error, true, Self::parse_unary_body)
+    }
+    fn parse_unary_body(&mut self) -> Node<Expression> {
+        let token = try_peek_or_error!(parser: self, error: Expression::Error);
+        let variant = match token.variant {
+            TokenVariant::Minus => UnaryVariant::NegateNumber,
+            TokenVariant::Exclamation => UnaryVariant::NegateBool,
+            _ => return self.parse_member_index_call(),
         };
-        let position = Position { ..left.position };
-        self.iter.next().expect("already peeked");
-        let right = self.parse_equality();
+        let token = self.iter.next().unwrap();
+        let subject = self.parse_unary();
         Self::node(
-            Expression::Binary {
-                left: Box::new(left),
-                right: Box::new(right),
+            Expression::Unary {
+                subject: Box::new(subject),
                 variant,
             },
+            (&token).into(),
+        )
+    }
+    fn parse_member_index_call(&mut self) -> Node<Expression> {
+        let subject = self.parse_grouping();
+        self.parse_member_index_call_from(subject)
+    }
+    fn parse_member_index_call_from(&mut self, mut subject: Node<Expression>) -> Node<Expression> {
+        loop {
+            let Some(token) = self.iter.peek() else {
+                break subject;
+            };
+            match token.variant {
+                TokenVariant::LParenthesis => subject = self.parse_call(subject),
+                TokenVariant::LBracket => subject = self.parse_index(subject),
+                TokenVariant::Dot => subject = self.parse_member(subject),
+                TokenVariant::DoubleColon => subject = self.parse_path(subject),
+                _ => break subject,
+            }
+        }
+    }
+    fn parse_call(&mut self, subject: Node<Expression>) -> Node<Expression> {
+        let parenthesis = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            parenthesis.variant,
+            TokenVariant::LParenthesis,
+            "called out of order"
+        );
+        let position = subject.position.clone();
+
+        let mut arguments = Vec::new();
+        loop {
+            let next =
+                try_peek_or_error!(parser: self, expect: RParenthesis, error: Expression::Error);
+            if next.variant == TokenVariant::RParenthesis {
+                self.iter.next().expect("already peeked");
+                break;
+            }
+            arguments.push(self.with_struct_literals(true, Self::parse_expression));
+            let next =
+                try_peek_or_error!(parser: self, expect: RParenthesis, error: Expression::Error);
+            match next.variant {
+                TokenVariant::Comma => {
+                    self.iter.next().expect("already peeked");
+                }
+                _ => assert_equal_variant!(next == RParenthesis, error: Expression::Error),
+            }
+        }
+
+        Self::node(
+            Expression::Call {
+                subject: Box::new(subject),
+                arguments,
+            },
             position,
         )
     }
+    fn parse_index(&mut self, subject: Node<Expression>) -> Node<Expression> {
+        let bracket = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            bracket.variant,
+            TokenVariant::LBracket,
+            "called out of order"
+        );
+        let position = subject.position.clone();
+
+        try_peek_or_error!(parser: self, error: Expression::Error);
+        let index = self.with_struct_literals(true, Self::parse_expression);
+        if let Expression::Error(_) = index.value {
+            return index;
+        }
+        let next = try_peek_or_error!(parser: self, expect: RBracket, error: Expression::Error);
+        assert_equal_variant!(next == RBracket, error: Expression::Error);
+        self.iter.next().expect("already peeked");
 
-    fn parse_add_subtract(&mut self) -> Node<Expression> {
-        let left = self.parse_multiply_divide();
-        let Some(operand) = self.iter.peek() else {
-            return left;
-        };
-        let variant = match operand.variant {
-            TokenVariant::Plus => BinaryVariant::Addition,
-            TokenVariant::Minus => BinaryVariant::Subtraction,
-            _ => return left,
-        };
-        let position = Position { ..left.position };
-        self.iter.next().unwrap();
-        let right = self.parse_add_subtract();
         Self::node(
-            Expression::Binary {
-                left: Box::new(left),
-                right: Box::new(right),
-                variant,
+            Expression::Index {
+                subject: Box::new(subject),
+                index: Box::new(index),
             },
             position,
         )
     }
-    fn parse_multiply_divide(&mut self) -> Node<Expression> {
-        let left = self.parse_unary();
-        let Some(operand) = self.iter.peek() else {
-            return left;
-        };
-        let variant = match operand.variant {
-            TokenVariant::Asterisk => BinaryVariant::Multiplication,
-            TokenVariant::Slash => BinaryVariant::Division,
-            _ => return left,
-        };
-        let position = Position { ..left.position };
-        self.iter.next().unwrap();
-        let right = self.parse_multiply_divide();
+    fn parse_member(&mut self, subject: Node<Expression>) -> Node<Expression> {
+        let dot = self.iter.next().expect("called out of order");
+        debug_assert_eq!(dot.variant, TokenVariant::Dot, "called out of order");
+        let position = subject.position.clone();
+
+        let next = try_peek_or_error!(parser: self, expect: Identifier, error: Expression::Error);
+        assert_equal_variant!(next == Identifier, error: Expression::Error);
+        let member = self.parse_identifier();
+
         Self::node(
-            Expression::Binary {
-                left: Box::new(left),
-                right: Box::new(right),
-                variant,
+            Expression::Member {
+                subject: Box::new(subject),
+                member: Box::new(member),
             },
             position,
         )
     }
-    fn parse_unary(&mut self) -> Node<Expression> {
-        let token = try_peek_or_error!(parser: self, error: Expression::Error);
-        let variant = match token.variant {
-            TokenVariant::Minus => UnaryVariant::NegateNumber,
-            TokenVariant::Exclamation => UnaryVariant::NegateBool,
-            _ => return self.parse_operand(), // TODO: member index call;
-        };
-        let token = self.iter.next().unwrap();
-        let subject = self.parse_unary();
+    fn parse_path(&mut self, subject: Node<Expression>) -> Node<Expression> {
+        let colons = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            colons.variant,
+            TokenVariant::DoubleColon,
+            "called out of order"
+        );
+        let position = subject.position.clone();
+
+        let next = try_peek_or_error!(parser: self, expect: Identifier, error: Expression::Error);
+        assert_equal_variant!(next == Identifier, error: Expression::Error);
+        let member = self.parse_identifier();
+
         Self::node(
-            Expression::Unary {
+            Expression::Path {
                 subject: Box::new(subject),
-                variant,
+                member: Box::new(member),
             },
-            (&token).into(),
+            position,
+        )
+    }
+    pub fn parse_match(&mut self) -> Node<Expression> {
+        let keyword = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            keyword.variant,
+            TokenVariant::MatchKeyword,
+            "called out of order"
+        );
+        let position: Position = (&keyword).into();
+
+        try_peek_or_error!(parser: self, error: Expression::Error);
+        let subject = self.with_struct_literals(false, Self::parse_expression);
+        if let Expression::Error(_) = subject.value {
+            return subject;
+        }
+
+        let next = try_peek_or_error!(parser: self, expect: LBrace, error: Expression::Error);
+        assert_equal_variant!(next == LBrace, error: Expression::Error);
+        self.iter.next().expect("already peeked");
+
+        let mut arms = Vec::new();
+        loop {
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Expression::Error);
+            if next.variant == TokenVariant::RBrace {
+                self.iter.next().expect("already peeked");
+                break;
+            }
+            let pattern = match self.parse_pattern() {
+                Node {
+                    value: Pattern::Error(message),
+                    position,
+                } => return Self::node(Expression::Error(message), position),
+                pattern => pattern,
+            };
+
+            let next =
+                try_peek_or_error!(parser: self, expect: EqualGreater, error: Expression::Error);
+            let guard = match next.variant {
+                TokenVariant::IfKeyword => {
+                    self.iter.next().expect("already peeked");
+                    try_peek_or_error!(parser: self, error: Expression::Error);
+                    let guard = self.with_struct_literals(true, Self::parse_expression);
+                    if let Expression::Error(_) = guard.value {
+                        return guard;
+                    }
+                    Some(guard)
+                }
+                _ => None,
+            };
+
+            let next =
+                try_peek_or_error!(parser: self, expect: EqualGreater, error: Expression::Error);
+            assert_equal_variant!(next == EqualGreater, error: Expression::Error);
+            self.iter.next().expect("already peeked");
+
+            try_peek_or_error!(parser: self, error: Expression::Error);
+            let body = self.with_struct_literals(true, Self::parse_expression);
+            if let Expression::Error(_) = body.value {
+                return body;
+            }
+            let block_like = is_block_like(&body.value);
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body,
+            });
+
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Expression::Error);
+            match next.variant {
+                TokenVariant::Comma => {
+                    self.iter.next().expect("already peeked");
+                }
+                _ if block_like => {}
+                _ => assert_equal_variant!(next == RBrace, error: Expression::Error),
+            }
+        }
+
+        Self::node(
+            Expression::Match {
+                subject: Box::new(subject),
+                arms,
+            },
+            position,
+        )
+    }
+    pub fn parse_pattern(&mut self) -> Node<Pattern> {
+        self.nested(Pattern::Error, true, Self::parse_pattern_body)
+    }
+    fn parse_pattern_body(&mut self) -> Node<Pattern> {
+        let token = try_peek_or_error!(parser: self, error: Pattern::Error);
+        let position: Position = token.into();
+        match token.variant {
+            TokenVariant::Identifier => {}
+            TokenVariant::Integer
+            | TokenVariant::Float
+            | TokenVariant::String
+            | TokenVariant::TrueKeyword
+            | TokenVariant::FalseKeyword => {
+                let literal = self.parse_operand();
+                return match literal.value {
+                    Expression::Error(message) => {
+                        Self::node(Pattern::Error(message), literal.position)
+                    }
+                    value => Self::node(Pattern::Literal(value), position),
+                };
+            }
+            TokenVariant::Minus => {
+                self.iter.next().expect("already peeked");
+                let next = try_peek_or_error!(parser: self, expect: Integer, error: Pattern::Error);
+                if next.variant != TokenVariant::Float {
+                    assert_equal_variant!(next == Integer, error: Pattern::Error);
+                }
+                let literal = self.parse_operand();
+                let value = match literal.value {
+                    Expression::Integer(value) => Pattern::Literal(Expression::Integer(-value)),
+                    Expression::Float(value) => Pattern::Literal(Expression::Float(-value)),
+                    Expression::Error(message) => {
+                        return Self::node(Pattern::Error(message), literal.position)
+                    }
+                    value => panic!("expected Integer or Float, got {value:#?}"),
+                };
+                return Self::node(value, position);
+            }
+            ref variant => {
+                let message = format!("unexpected pattern {variant:#?}");
+                self.iter.next().expect("already peeked");
+                return Self::node(Pattern::Error(message), position);
+            }
+        }
+
+        let identifier = self.parse_identifier();
+        match (&identifier.value, self.iter.peek()) {
+            (
+                _,
+                Some(Token {
+                    variant: TokenVariant::DoubleColon,
+                    ..
+                }),
+            ) => self.parse_variant_pattern(identifier),
+            (Expression::Identifier(name), _) if name == "_" => {
+                Self::node(Pattern::Wildcard, position)
+            }
+            (Expression::Identifier(name), _) => {
+                Self::node(Pattern::Identifier(name.clone()), position)
+            }
+            (value, _) => panic!("expected Identifier, got {value:#?}"),
+        }
+    }
+    fn parse_variant_pattern(&mut self, enumeration: Node<Expression>) -> Node<Pattern> {
+        let colons = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            colons.variant,
+            TokenVariant::DoubleColon,
+            "called out of order"
+        );
+        let position = enumeration.position.clone();
+
+        let next = try_peek_or_error!(parser: self, expect: Identifier, error: Pattern::Error);
+        assert_equal_variant!(next == Identifier, error: Pattern::Error);
+        let variant = self.parse_identifier();
+
+        let mut fields = Vec::new();
+        if let Some(Token {
+            variant: TokenVariant::LParenthesis,
+            ..
+        }) = self.iter.peek()
+        {
+            self.iter.next().expect("already peeked");
+            loop {
+                let next =
+                    try_peek_or_error!(parser: self, expect: RParenthesis, error: Pattern::Error);
+                if next.variant == TokenVariant::RParenthesis {
+                    self.iter.next().expect("already peeked");
+                    break;
+                }
+                let field = self.parse_pattern();
+                if let Pattern::Error(_) = field.value {
+                    return field;
+                }
+                fields.push(field);
+                let next =
+                    try_peek_or_error!(parser: self, expect: RParenthesis, error: Pattern::Error);
+                match next.variant {
+                    TokenVariant::Comma => {
+                        self.iter.next().expect("already peeked");
+                    }
+                    _ => assert_equal_variant!(next == RParenthesis, error: Pattern::Error),
+                }
+            }
+        }
+
+        Self::node(
+            Pattern::Variant {
+                enumeration: Box::new(enumeration),
+                variant: Box::new(variant),
+                fields,
+            },
+            position,
         )
     }
+    fn parse_block_or_map(&mut self) -> Node<Expression> {
+        let brace = self.iter.next().expect("called out of order");
+        debug_assert_eq!(brace.variant, TokenVariant::LBrace, "called out of order");
+        let position: Position = (&brace).into();
+
+        let next = try_peek_or_error!(parser: self, expect: RBrace, error: Expression::Error);
+        match next.variant {
+            TokenVariant::Colon => {
+                self.iter.next().expect("already peeked");
+                let next =
+                    try_peek_or_error!(parser: self, expect: RBrace, error: Expression::Error);
+                assert_equal_variant!(next == RBrace, error: Expression::Error);
+                self.iter.next().expect("already peeked");
+                return Self::node(Expression::Map(Vec::new()), position);
+            }
+            TokenVariant::String => {}
+            _ => return self.parse_block_rest(Vec::new(), position),
+        }
+
+        let first = self.parse_expression();
+        if let Expression::Error(_) = first.value {
+            return first;
+        }
+        match (&first.value, self.iter.peek()) {
+            (
+                Expression::String(_),
+                Some(Token {
+                    variant: TokenVariant::Colon,
+                    ..
+                }),
+            ) => self.parse_map_entries(first, position),
+            _ => {
+                let statement = self.parse_assignment_from(first);
+                self.parse_block_rest(vec![statement], position)
+            }
+        }
+    }
+    fn parse_map_entries(
+        &mut self,
+        first_key: Node<Expression>,
+        position: Position,
+    ) -> Node<Expression> {
+        let mut entries = Vec::new();
+        let mut key = first_key;
+        loop {
+            let next = try_peek_or_error!(parser: self, expect: Colon, error: Expression::Error);
+            assert_equal_variant!(next == Colon, error: Expression::Error);
+            self.iter.next().expect("already peeked");
+
+            try_peek_or_error!(parser: self, error: Expression::Error);
+            let value = self.parse_expression();
+            if let Expression::Error(_) = value.value {
+                return value;
+            }
+            entries.push((key, value));
+
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Expression::Error);
+            match next.variant {
+                TokenVariant::Comma => {
+                    self.iter.next().expect("already peeked");
+                }
+                _ => assert_equal_variant!(next == RBrace, error: Expression::Error),
+            }
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Expression::Error);
+            if next.variant == TokenVariant::RBrace {
+                self.iter.next().expect("already peeked");
+                break;
+            }
+            key = self.parse_expression();
+            if let Expression::Error(_) = key.value {
+                return key;
+            }
+        }
+
+        Self::node(Expression::Map(entries), position)
+    }
+    fn parse_list(&mut self) -> Node<Expression> {
+        let bracket = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            bracket.variant,
+            TokenVariant::LBracket,
+            "called out of order"
+        );
+        let position: Position = (&bracket).into();
+
+        let mut elements = Vec::new();
+        loop {
+            let next = try_peek_or_error!(parser: self, expect: RBracket, error: Expression::Error);
+            if next.variant == TokenVariant::RBracket {
+                self.iter.next().expect("already peeked");
+                break;
+            }
+            let element = self.with_struct_literals(true, Self::parse_expression);
+            if let Expression::Error(_) = element.value {
+                return element;
+            }
+            elements.push(element);
+            let next = try_peek_or_error!(parser: self, expect: RBracket, error: Expression::Error);
+            match next.variant {
+                TokenVariant::Comma => {
+                    self.iter.next().expect("already peeked");
+                }
+                _ => assert_equal_variant!(next == RBracket, error: Expression::Error),
+            }
+        }
+
+        Self::node(Expression::List(elements), position)
+    }
+    fn parse_grouping(&mut self) -> Node<Expression> {
+        let token = try_peek_or_error!(parser: self, error: Expression::Error);
+        if token.variant != TokenVariant::LParenthesis {
+            return self.parse_operand();
+        }
+        self.iter.next().expect("already peeked");
+        let expression = self.with_struct_literals(true, Self::parse_expression);
+        let next = try_peek_or_error!(parser: self, expect: RParenthesis, error: Expression::Error);
+        assert_equal_variant!(next == RParenthesis, error: Expression::Error);
+        self.iter.next().expect("already peeked");
+        expression
+    }
     fn parse_operand(&mut self) -> Node<Expression> {
         let token = self.iter.peek().unwrap();
         match &token.variant {
             TokenVariant::Identifier => {
-                let token = self.iter.next().unwrap();
-                let value = self.text[token.index..token.index + token.length].to_owned();
-                Self::node(Expression::Identifier(value), (&token).into())
+                let identifier = self.parse_identifier();
+                match self.iter.peek() {
+                    Some(Token {
+                        variant: TokenVariant::LBrace,
+                        ..
+                    }) if self.struct_literals => self.parse_struct_literal(identifier),
+                    _ => identifier,
+                }
             }
             TokenVariant::Integer => {
                 let token = self.iter.next().unwrap();
-                let value = &self.text[token.index..token.index + token.length]
-                    .parse::<i64>()
-                    .unwrap();
-                Self::node(Expression::Integer(*value), (&token).into())
+                let text = &self.text[token.index..token.index + token.length];
+                let value = match text.parse::<i64>() {
+                    Ok(value) => Expression::Integer(value),
+                    Err(_) => Expression::Error(format!("integer literal {text} is out of range")),
+                };
+                Self::node(value, (&token).into())
+            }
+            TokenVariant::TrueKeyword => {
+                let token = self.iter.next().unwrap();
+                Self::node(Expression::Bool(true), (&token).into())
+            }
+            TokenVariant::FalseKeyword => {
+                let token = self.iter.next().unwrap();
+                Self::node(Expression::Bool(false), (&token).into())
+            }
+            TokenVariant::IfKeyword => self.parse_if(),
+            TokenVariant::MatchKeyword => self.parse_match(),
+            TokenVariant::LBrace => self.parse_block_or_map(),
+            TokenVariant::LBracket => self.parse_list(),
+            TokenVariant::FnKeyword => {
+                let token = self.iter.next().unwrap();
+                self.parse_function_literal((&token).into())
+            }
+            TokenVariant::String => self.parse_string(),
+            TokenVariant::UnterminatedString => {
+                let token = self.iter.next().unwrap();
+                Self::node(
+                    Expression::Error("unterminated string literal".to_string()),
+                    (&token).into(),
+                )
             }
             TokenVariant::Float => {
                 let token = self.iter.next().unwrap();
@@ -391,50 +1512,249 @@ where
             op => {
                 let value = format!("unexpected operand {op:#?}");
                 let token = self.iter.next().unwrap();
-                Self::node(Expression::Identifier(value), (&token).into())
+                Self::node(Expression::Error(value), (&token).into())
             }
         }
     }
-    fn node<T>(value: T, position: Position) -> Node<T> {
-        Node { value, position }
+    fn parse_identifier(&mut self) -> Node<Expression> {
+        let token = self.iter.next().expect("called out of order");
+        debug_assert_eq!(
+            token.variant,
+            TokenVariant::Identifier,
+            "called out of order"
+        );
+        let value = self.text[token.index..token.index + token.length].to_owned();
+        Self::node(Expression::Identifier(value), (&token).into())
     }
-}
+    fn parse_struct_literal(&mut self, identifier: Node<Expression>) -> Node<Expression> {
+        let brace = self.iter.next().expect("called out of order");
+        debug_assert_eq!(brace.variant, TokenVariant::LBrace, "called out of order");
+        let position = identifier.position.clone();
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use couch_lang_lexer::Lexer;
-    use pretty_assertions::assert_eq;
+        let mut fields = Vec::new();
+        loop {
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Expression::Error);
+            if next.variant == TokenVariant::RBrace {
+                self.iter.next().expect("already peeked");
+                break;
+            }
+            assert_equal_variant!(next == Identifier, error: Expression::Error);
+            let field = self.parse_identifier();
 
-    #[test]
-    fn parse_integer() {
-        let input = String::from("1");
-        let lexer = Lexer::new(input.chars());
-        let mut parser = Parser {
-            iter: lexer.into_iter().peekable(),
-            text: input.clone(),
-        };
-        let expression = parser.parse_expression();
-        assert_eq!(
-            expression,
-            Node {
-                position: Position {
-                    column: 1,
-                    index: 0,
-                    line: 1,
-                },
-                value: Expression::Integer(1)
+            let next = try_peek_or_error!(parser: self, expect: Colon, error: Expression::Error);
+            assert_equal_variant!(next == Colon, error: Expression::Error);
+            self.iter.next().expect("already peeked");
+
+            try_peek_or_error!(parser: self, error: Expression::Error);
+            let value = self.with_struct_literals(true, Self::parse_expression);
+            if let Expression::Error(_) = value.value {
+                return value;
             }
-        )
-    }
+            fields.push((field, value));
 
-    #[test]
-    fn parse_negative_integer() {
-        let input = String::from("-1");
-        let lexer = Lexer::new(input.chars());
-        let mut parser = Parser {
-            iter: lexer.into_iter().peekable(),
+            let next = try_peek_or_error!(parser: self, expect: RBrace, error: Expression::Error);
+            match next.variant {
+                TokenVariant::Comma => {
+                    self.iter.next().expect("already peeked");
+                }
+                _ => assert_equal_variant!(next == RBrace, error: Expression::Error),
+            }
+        }
+
+        Self::node(
+            Expression::Struct {
+                identifier: Box::new(identifier),
+                fields,
+            },
+            position,
+        )
+    }
+    fn nested<T>(
+        &mut self,
+        error: fn(String) -> T,
+        expression: bool,
+        parse: impl FnOnce(&mut Self) -> Node<T>,
+    ) -> Node<T> {
+        if self.depth >= MAX_NESTING_DEPTH {
+            if let Some(token) = self.iter.peek() {
+                let position: Position = token.into();
+                self.skip_nested(expression);
+                let message = format!("maximum nesting depth of {MAX_NESTING_DEPTH} exceeded");
+                return Self::node(error(message), position);
+            }
+        }
+        self.depth += 1;
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || parse(self));
+        self.depth -= 1;
+        result
+    }
+    /// Skips the rest of a construct nested too deeply: up to the closing
+    /// bracket of the enclosing one, or for expressions up to the next `;`.
+    fn skip_nested(&mut self, expression: bool) {
+        let mut open = 0;
+        let mut skipped = false;
+        while let Some(token) = self.iter.peek() {
+            match token.variant {
+                TokenVariant::LParenthesis | TokenVariant::LBracket | TokenVariant::LBrace => {
+                    open += 1
+                }
+                TokenVariant::RParenthesis | TokenVariant::RBracket | TokenVariant::RBrace
+                    if open == 0 && skipped =>
+                {
+                    break
+                }
+                TokenVariant::RParenthesis | TokenVariant::RBracket | TokenVariant::RBrace
+                    if open > 0 =>
+                {
+                    open -= 1
+                }
+                TokenVariant::Semicolon if expression && open == 0 && skipped => break,
+                _ => {}
+            }
+            self.iter.next().expect("already peeked");
+            skipped = true;
+        }
+    }
+    fn with_struct_literals<T>(&mut self, allowed: bool, parse: impl FnOnce(&mut Self) -> T) -> T {
+        let previous = std::mem::replace(&mut self.struct_literals, allowed);
+        let result = parse(self);
+        self.struct_literals = previous;
+        result
+    }
+    fn parse_string(&mut self) -> Node<Expression> {
+        let token = self.iter.next().expect("called out of order");
+        debug_assert_eq!(token.variant, TokenVariant::String, "called out of order");
+        let literal = &self.text[token.index + 1..token.index + token.length - 1];
+
+        let escape_position = |offset: usize| {
+            let before = &self.text[token.index..token.index + 1 + offset];
+            let line = token.line + before.matches('\n').count();
+            let column = match before.rsplit_once('\n') {
+                Some((_, after_newline)) => after_newline.chars().count() + 1,
+                None => token.column + before.chars().count(),
+            };
+            Position {
+                index: token.index + 1 + offset,
+                line,
+                column,
+            }
+        };
+
+        let mut value = String::new();
+        let mut chars = literal.char_indices();
+        while let Some((offset, char)) = chars.next() {
+            if char != '\\' {
+                value.push(char);
+                continue;
+            }
+            let escaped = match chars.next() {
+                Some((_, 'n')) => Some('\n'),
+                Some((_, 't')) => Some('\t'),
+                Some((_, '"')) => Some('"'),
+                Some((_, '\\')) => Some('\\'),
+                Some((_, 'u')) => Self::parse_unicode_escape(&mut chars),
+                _ => None,
+            };
+            let Some(escaped) = escaped else {
+                let sequence = literal[offset..]
+                    .chars()
+                    .take_while(|char| !char.is_whitespace())
+                    .take(10)
+                    .collect::<String>();
+                let message = format!("invalid escape sequence '{sequence}'");
+                return Self::node(Expression::Error(message), escape_position(offset));
+            };
+            value.push(escaped);
+        }
+
+        Self::node(Expression::String(value), (&token).into())
+    }
+    fn parse_unicode_escape(chars: &mut std::str::CharIndices) -> Option<char> {
+        if chars.next()?.1 != '{' {
+            return None;
+        }
+        let mut digits = String::new();
+        loop {
+            match chars.next()?.1 {
+                '}' => break,
+                digit if digit.is_ascii_hexdigit() && digits.len() < 6 => digits.push(digit),
+                _ => return None,
+            }
+        }
+        char::from_u32(u32::from_str_radix(&digits, 16).ok()?)
+    }
+    fn expression_error(&mut self, expression: &Node<Expression>) -> Option<Node<Statement>> {
+        let Expression::Error(message) = &expression.value else {
+            return None;
+        };
+        if let Some(Token {
+            variant: TokenVariant::Semicolon,
+            ..
+        }) = self.iter.peek()
+        {
+            self.iter.next().expect("already peeked");
+        }
+        Some(Self::node(
+            Statement::Error(message.clone()),
+            expression.position.clone(),
+        ))
+    }
+    fn node<T>(value: T, position: Position) -> Node<T> {
+        Node { value, position }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use couch_lang_lexer::Lexer;
+    use pretty_assertions::assert_eq;
+
+    /// A node on the first line, where the column follows from the index.
+    fn node<T>(value: T, index: usize) -> Node<T> {
+        Node {
+            value,
+            position: Position {
+                index,
+                line: 1,
+                column: index + 1,
+            },
+        }
+    }
+
+    #[test]
+    fn parse_integer() {
+        let input = String::from("1");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser {
+            iter: lexer.into_iter().peekable(),
+            text: input.clone(),
+            struct_literals: true,
+            depth: 0,
+        };
+        let expression = parser.parse_expression();
+        assert_eq!(
+            expression,
+            Node {
+                position: Position {
+                    column: 1,
+                    index: 0,
+                    line: 1,
+                },
+                value: Expression::Integer(1)
+            }
+        )
+    }
+
+    #[test]
+    fn parse_negative_integer() {
+        let input = String::from("-1");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser {
+            iter: lexer.into_iter().peekable(),
             text: input.clone(),
+            struct_literals: true,
+            depth: 0,
         };
         let expression = parser.parse_expression();
         assert_eq!(
@@ -467,6 +1787,8 @@ mod tests {
         let mut parser = Parser {
             iter: lexer.into_iter().peekable(),
             text: input.clone(),
+            struct_literals: true,
+            depth: 0,
         };
         let expression = parser.parse_expression();
         assert_eq!(
@@ -479,40 +1801,40 @@ mod tests {
                 },
                 value: Expression::Binary {
                     left: Box::new(Node {
-                        value: Expression::Integer(20),
                         position: Position {
                             index: 0,
-                            line: 1,
-                            column: 1
-                        },
-                    }),
-                    right: Box::new(Node {
-                        position: Position {
-                            index: 5,
-                            column: 6,
+                            column: 1,
                             line: 1,
                         },
                         value: Expression::Binary {
                             left: Box::new(Node {
-                                value: Expression::Integer(27),
+                                value: Expression::Integer(20),
                                 position: Position {
-                                    index: 5,
-                                    column: 6,
+                                    index: 0,
                                     line: 1,
+                                    column: 1
                                 },
                             }),
                             right: Box::new(Node {
-                                value: Expression::Float(49.5),
+                                value: Expression::Integer(27),
                                 position: Position {
-                                    index: 10,
+                                    index: 5,
+                                    column: 6,
                                     line: 1,
-                                    column: 11,
                                 },
                             }),
-                            variant: BinaryVariant::Subtraction,
+                            variant: BinaryVariant::Addition,
                         }
                     }),
-                    variant: BinaryVariant::Addition,
+                    right: Box::new(Node {
+                        value: Expression::Float(49.5),
+                        position: Position {
+                            index: 10,
+                            line: 1,
+                            column: 11,
+                        },
+                    }),
+                    variant: BinaryVariant::Subtraction,
                 }
             }
         )
@@ -525,6 +1847,8 @@ mod tests {
         let mut parser = Parser {
             iter: lexer.into_iter().peekable(),
             text: input.clone(),
+            struct_literals: true,
+            depth: 0,
         };
         let expression = parser.parse_expression();
         assert_eq!(
@@ -583,6 +1907,8 @@ mod tests {
         let mut parser = Parser {
             iter: lexer.into_iter().peekable(),
             text: input.clone(),
+            struct_literals: true,
+            depth: 0,
         };
         let expression = parser.parse_statements();
         assert_eq!(
@@ -605,6 +1931,8 @@ mod tests {
         let mut parser = Parser {
             iter: lexer.into_iter().peekable(),
             text: input.clone(),
+            struct_literals: true,
+            depth: 0,
         };
         let expression = parser.parse_statements();
         assert_eq!(
@@ -652,6 +1980,8 @@ mod tests {
         let mut parser = Parser {
             iter: lexer.into_iter().peekable(),
             text: input.clone(),
+            struct_literals: true,
+            depth: 0,
         };
         let expression = parser.parse_statements();
         assert_eq!(
@@ -667,6 +1997,7 @@ mod tests {
                             column: 5,
                         },
                     }),
+                    annotation: None,
                     value: Box::new(Node {
                         value: Expression::Identifier("b".to_string()),
                         position: Position {
@@ -692,6 +2023,8 @@ mod tests {
         let mut parser = Parser {
             iter: lexer.into_iter().peekable(),
             text: input.clone(),
+            struct_literals: true,
+            depth: 0,
         };
         let expression = parser.parse_statements();
         assert_eq!(
@@ -707,6 +2040,7 @@ mod tests {
                             column: 9,
                         },
                     }),
+                    annotation: None,
                     value: Box::new(Node {
                         value: Expression::Identifier("b".to_string()),
                         position: Position {
@@ -819,6 +2153,7 @@ mod tests {
                                 column: 9
                             }
                         }),
+                        annotation: None,
                         value: Box::new(Node {
                             value: Expression::Integer(5),
                             position: Position {
@@ -904,4 +2239,1386 @@ mod tests {
             }]
         );
     }
+
+    #[test]
+    fn parse_function() {
+        let input = String::from("fn add(a, mut b) { return a; }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        assert_eq!(
+            statements,
+            vec![Node {
+                value: Statement::Function {
+                    identifier: Box::new(Node {
+                        value: Expression::Identifier("add".to_string()),
+                        position: Position {
+                            index: 3,
+                            line: 1,
+                            column: 4,
+                        },
+                    }),
+                    parameters: vec![
+                        Node {
+                            value: Parameter::Item {
+                                mutable: false,
+                                identifier: Box::new(Node {
+                                    value: Expression::Identifier("a".to_string()),
+                                    position: Position {
+                                        index: 7,
+                                        line: 1,
+                                        column: 8,
+                                    },
+                                }),
+                                annotation: None,
+                            },
+                            position: Position {
+                                index: 7,
+                                line: 1,
+                                column: 8,
+                            },
+                        },
+                        Node {
+                            value: Parameter::Item {
+                                mutable: true,
+                                identifier: Box::new(Node {
+                                    value: Expression::Identifier("b".to_string()),
+                                    position: Position {
+                                        index: 14,
+                                        line: 1,
+                                        column: 15,
+                                    },
+                                }),
+                                annotation: None,
+                            },
+                            position: Position {
+                                index: 10,
+                                line: 1,
+                                column: 11,
+                            },
+                        },
+                    ],
+                    return_type: None,
+                    body: Box::new(Node {
+                        value: Expression::Block(vec![Node {
+                            value: Statement::Return(Some(Box::new(Node {
+                                value: Expression::Identifier("a".to_string()),
+                                position: Position {
+                                    index: 26,
+                                    line: 1,
+                                    column: 27,
+                                },
+                            }))),
+                            position: Position {
+                                index: 19,
+                                line: 1,
+                                column: 20,
+                            },
+                        }]),
+                        position: Position {
+                            index: 17,
+                            line: 1,
+                            column: 18,
+                        },
+                    }),
+                },
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_function_missing_brace() {
+        let input = String::from("fn f(a,) { a;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        assert!(matches!(
+            statements.as_slice(),
+            [Node {
+                value: Statement::Error(_),
+                ..
+            }]
+        ));
+    }
+
+    #[test]
+    fn parse_call() {
+        let input = String::from("f(1, a,)");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+        assert_eq!(
+            expression,
+            Node {
+                value: Expression::Call {
+                    subject: Box::new(Node {
+                        value: Expression::Identifier("f".to_string()),
+                        position: Position {
+                            index: 0,
+                            line: 1,
+                            column: 1,
+                        },
+                    }),
+                    arguments: vec![
+                        Node {
+                            value: Expression::Integer(1),
+                            position: Position {
+                                index: 2,
+                                line: 1,
+                                column: 3,
+                            },
+                        },
+                        Node {
+                            value: Expression::Identifier("a".to_string()),
+                            position: Position {
+                                index: 5,
+                                line: 1,
+                                column: 6,
+                            },
+                        },
+                    ],
+                },
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn parse_grouping() {
+        let input = String::from("(1 + 2) * 3");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+        assert_eq!(
+            expression,
+            Node {
+                value: Expression::Binary {
+                    left: Box::new(Node {
+                        value: Expression::Binary {
+                            left: Box::new(Node {
+                                value: Expression::Integer(1),
+                                position: Position {
+                                    index: 1,
+                                    line: 1,
+                                    column: 2,
+                                },
+                            }),
+                            right: Box::new(Node {
+                                value: Expression::Integer(2),
+                                position: Position {
+                                    index: 5,
+                                    line: 1,
+                                    column: 6,
+                                },
+                            }),
+                            variant: BinaryVariant::Addition,
+                        },
+                        position: Position {
+                            index: 1,
+                            line: 1,
+                            column: 2,
+                        },
+                    }),
+                    right: Box::new(Node {
+                        value: Expression::Integer(3),
+                        position: Position {
+                            index: 10,
+                            line: 1,
+                            column: 11,
+                        },
+                    }),
+                    variant: BinaryVariant::Multiplication,
+                },
+                position: Position {
+                    index: 1,
+                    line: 1,
+                    column: 2,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn parse_while() {
+        let input = String::from("while a { break; continue; }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        assert_eq!(
+            statements,
+            vec![Node {
+                value: Statement::While {
+                    condition: Box::new(Node {
+                        value: Expression::Identifier("a".to_string()),
+                        position: Position {
+                            index: 6,
+                            line: 1,
+                            column: 7,
+                        },
+                    }),
+                    body: Box::new(Node {
+                        value: Expression::Block(vec![
+                            Node {
+                                value: Statement::Break,
+                                position: Position {
+                                    index: 10,
+                                    line: 1,
+                                    column: 11,
+                                },
+                            },
+                            Node {
+                                value: Statement::Continue,
+                                position: Position {
+                                    index: 17,
+                                    line: 1,
+                                    column: 18,
+                                },
+                            },
+                        ]),
+                        position: Position {
+                            index: 8,
+                            line: 1,
+                            column: 9,
+                        },
+                    }),
+                },
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_if_else_if() {
+        let input = String::from("if true { } else if false { } else { }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+        assert_eq!(
+            expression,
+            Node {
+                value: Expression::If {
+                    condition: Box::new(Node {
+                        value: Expression::Bool(true),
+                        position: Position {
+                            index: 3,
+                            line: 1,
+                            column: 4,
+                        },
+                    }),
+                    truthy: Box::new(Node {
+                        value: Expression::Block(vec![]),
+                        position: Position {
+                            index: 8,
+                            line: 1,
+                            column: 9,
+                        },
+                    }),
+                    falsy: Some(Box::new(Node {
+                        value: Expression::If {
+                            condition: Box::new(Node {
+                                value: Expression::Bool(false),
+                                position: Position {
+                                    index: 20,
+                                    line: 1,
+                                    column: 21,
+                                },
+                            }),
+                            truthy: Box::new(Node {
+                                value: Expression::Block(vec![]),
+                                position: Position {
+                                    index: 26,
+                                    line: 1,
+                                    column: 27,
+                                },
+                            }),
+                            falsy: Some(Box::new(Node {
+                                value: Expression::Block(vec![]),
+                                position: Position {
+                                    index: 35,
+                                    line: 1,
+                                    column: 36,
+                                },
+                            })),
+                        },
+                        position: Position {
+                            index: 17,
+                            line: 1,
+                            column: 18,
+                        },
+                    })),
+                },
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn if_statement_without_semicolon() {
+        let input = String::from("if a { } b;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        assert_eq!(
+            statements,
+            vec![
+                Node {
+                    value: Statement::Expression(Node {
+                        value: Expression::If {
+                            condition: Box::new(Node {
+                                value: Expression::Identifier("a".to_string()),
+                                position: Position {
+                                    index: 3,
+                                    line: 1,
+                                    column: 4,
+                                },
+                            }),
+                            truthy: Box::new(Node {
+                                value: Expression::Block(vec![]),
+                                position: Position {
+                                    index: 5,
+                                    line: 1,
+                                    column: 6,
+                                },
+                            }),
+                            falsy: None,
+                        },
+                        position: Position {
+                            index: 0,
+                            line: 1,
+                            column: 1,
+                        },
+                    }),
+                    position: Position {
+                        index: 0,
+                        line: 1,
+                        column: 1,
+                    },
+                },
+                Node {
+                    value: Statement::Expression(Node {
+                        value: Expression::Identifier("b".to_string()),
+                        position: Position {
+                            index: 9,
+                            line: 1,
+                            column: 10,
+                        },
+                    }),
+                    position: Position {
+                        index: 9,
+                        line: 1,
+                        column: 10,
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_string_escapes() {
+        let input = String::from(r#""å\n\t\"\\\u{1F600}" a"#);
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        assert_eq!(
+            parser.parse_expression(),
+            Node {
+                value: Expression::String("å\n\t\"\\😀".to_string()),
+                position: Position {
+                    index: 0,
+                    line: 1,
+                    column: 1,
+                },
+            }
+        );
+        assert_eq!(
+            parser.parse_expression(),
+            Node {
+                value: Expression::Identifier("a".to_string()),
+                position: Position {
+                    index: 22,
+                    line: 1,
+                    column: 22,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn parse_invalid_string_escape() {
+        let input = String::from(r#""ab\q" "\u{110000}""#);
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        assert_eq!(
+            parser.parse_expression(),
+            Node {
+                value: Expression::Error(r"invalid escape sequence '\q'".to_string()),
+                position: Position {
+                    index: 3,
+                    line: 1,
+                    column: 4,
+                },
+            }
+        );
+        assert_eq!(
+            parser.parse_expression(),
+            Node {
+                value: Expression::Error(r"invalid escape sequence '\u{110000}'".to_string()),
+                position: Position {
+                    index: 8,
+                    line: 1,
+                    column: 9,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn parse_unterminated_string() {
+        let input = String::from("let a =\n  \"abc;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        assert_eq!(
+            statements,
+            vec![Node {
+                value: Statement::Error("unterminated string literal".to_string()),
+                position: Position {
+                    index: 10,
+                    line: 2,
+                    column: 3,
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_left_associative_chains() {
+        let input = String::from("10 - 3 - 2");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+        let Expression::Binary {
+            left,
+            right,
+            variant: BinaryVariant::Subtraction,
+        } = expression.value
+        else {
+            panic!("expected Subtraction, got {:#?}", expression.value);
+        };
+        assert_eq!(right.value, Expression::Integer(2));
+        assert!(matches!(
+            left.value,
+            Expression::Binary {
+                variant: BinaryVariant::Subtraction,
+                ..
+            }
+        ));
+
+        let input = String::from("8 / 4 / 2 == 1 != false");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+        let Expression::Binary {
+            left,
+            right,
+            variant: BinaryVariant::NotEqual,
+        } = expression.value
+        else {
+            panic!("expected NotEqual, got {:#?}", expression.value);
+        };
+        assert_eq!(right.value, Expression::Bool(false));
+        let Expression::Binary {
+            left,
+            right,
+            variant: BinaryVariant::Equal,
+        } = left.value
+        else {
+            panic!("expected Equal, got {:#?}", left.value);
+        };
+        assert_eq!(right.value, Expression::Integer(1));
+        let Expression::Binary {
+            left,
+            right,
+            variant: BinaryVariant::Division,
+        } = left.value
+        else {
+            panic!("expected Division, got {:#?}", left.value);
+        };
+        assert_eq!(right.value, Expression::Integer(2));
+        assert!(matches!(
+            left.value,
+            Expression::Binary {
+                variant: BinaryVariant::Division,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_logical_and_comparison_precedence() {
+        let input = String::from("a || b && c < d + 1");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+        let Expression::Binary {
+            left,
+            right,
+            variant: BinaryVariant::Or,
+        } = expression.value
+        else {
+            panic!("expected Or, got {:#?}", expression.value);
+        };
+        assert_eq!(left.value, Expression::Identifier("a".to_string()));
+        let Expression::Binary {
+            left,
+            right,
+            variant: BinaryVariant::And,
+        } = right.value
+        else {
+            panic!("expected And, got {:#?}", right.value);
+        };
+        assert_eq!(left.value, Expression::Identifier("b".to_string()));
+        let Expression::Binary {
+            left,
+            right,
+            variant: BinaryVariant::Less,
+        } = right.value
+        else {
+            panic!("expected Less, got {:#?}", right.value);
+        };
+        assert_eq!(left.value, Expression::Identifier("c".to_string()));
+        assert!(matches!(
+            right.value,
+            Expression::Binary {
+                variant: BinaryVariant::Addition,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_exponentiation_right_associative() {
+        let input = String::from("2 ** 3 ** 2");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+        let Expression::Binary {
+            left,
+            right,
+            variant: BinaryVariant::Exponentiation,
+        } = expression.value
+        else {
+            panic!("expected Exponentiation, got {:#?}", expression.value);
+        };
+        assert_eq!(left.value, Expression::Integer(2));
+        assert!(matches!(
+            right.value,
+            Expression::Binary {
+                variant: BinaryVariant::Exponentiation,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn parse_bitwise_precedence() {
+        let input = String::from("a | b ^ c & d << 1 == e");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let expression = parser.parse_expression();
+        let Expression::Binary {
+            left,
+            variant: BinaryVariant::Equal,
+            ..
+        } = expression.value
+        else {
+            panic!("expected Equal, got {:#?}", expression.value);
+        };
+        let Expression::Binary {
+            right,
+            variant: BinaryVariant::BitwiseOr,
+            ..
+        } = left.value
+        else {
+            panic!("expected BitwiseOr, got {:#?}", left.value);
+        };
+        let Expression::Binary {
+            right,
+            variant: BinaryVariant::BitwiseXor,
+            ..
+        } = right.value
+        else {
+            panic!("expected BitwiseXor, got {:#?}", right.value);
+        };
+        let Expression::Binary {
+            right,
+            variant: BinaryVariant::BitwiseAnd,
+            ..
+        } = right.value
+        else {
+            panic!("expected BitwiseAnd, got {:#?}", right.value);
+        };
+        assert!(matches!(
+            right.value,
+            Expression::Binary {
+                variant: BinaryVariant::LeftShift,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn integer_literal_out_of_range() {
+        let input = String::from("let a = 9223372036854775808;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        assert_eq!(
+            parser.parse_statements(),
+            vec![Node {
+                value: Statement::Error(
+                    "integer literal 9223372036854775808 is out of range".to_string()
+                ),
+                position: Position {
+                    index: 8,
+                    line: 1,
+                    column: 9
+                }
+            }]
+        );
+    }
+
+    #[test]
+    fn block_statement_and_expression() {
+        let input = String::from("{ a; } let b = { 1; };");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        assert_eq!(
+            statements,
+            vec![
+                Node {
+                    value: Statement::Expression(Node {
+                        value: Expression::Block(vec![Node {
+                            value: Statement::Expression(Node {
+                                value: Expression::Identifier("a".to_string()),
+                                position: Position {
+                                    index: 2,
+                                    line: 1,
+                                    column: 3,
+                                },
+                            }),
+                            position: Position {
+                                index: 2,
+                                line: 1,
+                                column: 3,
+                            },
+                        }]),
+                        position: Position {
+                            index: 0,
+                            line: 1,
+                            column: 1,
+                        },
+                    }),
+                    position: Position {
+                        index: 0,
+                        line: 1,
+                        column: 1,
+                    },
+                },
+                Node {
+                    value: Statement::Let {
+                        mutable: false,
+                        identifier: Box::new(Node {
+                            value: Expression::Identifier("b".to_string()),
+                            position: Position {
+                                index: 11,
+                                line: 1,
+                                column: 12,
+                            },
+                        }),
+                        annotation: None,
+                        value: Box::new(Node {
+                            value: Expression::Block(vec![Node {
+                                value: Statement::Expression(Node {
+                                    value: Expression::Integer(1),
+                                    position: Position {
+                                        index: 17,
+                                        line: 1,
+                                        column: 18,
+                                    },
+                                }),
+                                position: Position {
+                                    index: 17,
+                                    line: 1,
+                                    column: 18,
+                                },
+                            }]),
+                            position: Position {
+                                index: 15,
+                                line: 1,
+                                column: 16,
+                            },
+                        }),
+                    },
+                    position: Position {
+                        index: 7,
+                        line: 1,
+                        column: 8,
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn block_like_statements_end_without_semicolon() {
+        let cases = [
+            "{ let a = 1; }\n[0, 1];",
+            "{ println(\"block\"); }\n(1 + 2);",
+            "if true { 1; }\n-1;",
+            "match x { _ => 1 }\n[2];",
+        ];
+        for input in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statements = parser.parse_statements();
+            let [Node {
+                value: Statement::Expression(first),
+                ..
+            }, Node {
+                value: Statement::Expression(second),
+                ..
+            }] = statements.as_slice()
+            else {
+                panic!("{input}: expected two statements, got {statements:#?}");
+            };
+            assert!(is_block_like(&first.value), "{input}: {first:#?}");
+            assert!(
+                matches!(
+                    second.value,
+                    Expression::List(_) | Expression::Binary { .. } | Expression::Unary { .. }
+                ),
+                "{input}: {second:#?}"
+            );
+        }
+
+        let input = String::from("{ \"a\": [1] }.a[0] + 1;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let [Node {
+            value:
+                Statement::Expression(Node {
+                    value: Expression::Binary { left, .. },
+                    ..
+                }),
+            ..
+        }] = statements.as_slice()
+        else {
+            panic!("expected one binary statement, got {statements:#?}");
+        };
+        assert!(matches!(left.value, Expression::Index { .. }), "{left:#?}");
+    }
+
+    #[test]
+    fn parse_function_expression() {
+        let input = String::from("let f = fn(a, mut b) { b; };");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let [Node {
+            value: Statement::Let { value, .. },
+            ..
+        }] = statements.as_slice()
+        else {
+            panic!("expected a single Let, got {statements:#?}");
+        };
+        let Expression::Function {
+            parameters, body, ..
+        } = &value.value
+        else {
+            panic!("expected Function, got {:#?}", value.value);
+        };
+        assert_eq!(
+            value.position,
+            Position {
+                index: 8,
+                line: 1,
+                column: 9
+            }
+        );
+        assert_eq!(
+            parameters
+                .iter()
+                .map(|parameter| parameter.value.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                Parameter::Item {
+                    mutable: false,
+                    identifier: Box::new(Node {
+                        value: Expression::Identifier("a".to_string()),
+                        position: Position {
+                            index: 11,
+                            line: 1,
+                            column: 12
+                        }
+                    }),
+                    annotation: None,
+                },
+                Parameter::Item {
+                    mutable: true,
+                    identifier: Box::new(Node {
+                        value: Expression::Identifier("b".to_string()),
+                        position: Position {
+                            index: 18,
+                            line: 1,
+                            column: 19
+                        }
+                    }),
+                    annotation: None,
+                },
+            ]
+        );
+        assert!(matches!(&body.value, Expression::Block(statements) if statements.len() == 1));
+    }
+
+    #[test]
+    fn parse_list_and_index() {
+        let input = String::from("xs[0] = [1, 2,];");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let position = |index: usize| Position {
+            index,
+            line: 1,
+            column: index + 1,
+        };
+        assert_eq!(
+            statements,
+            vec![Node {
+                value: Statement::Assignment {
+                    left: Box::new(Node {
+                        value: Expression::Index {
+                            subject: Box::new(Node {
+                                value: Expression::Identifier("xs".to_string()),
+                                position: position(0),
+                            }),
+                            index: Box::new(Node {
+                                value: Expression::Integer(0),
+                                position: position(3),
+                            }),
+                        },
+                        position: position(0),
+                    }),
+                    right: Box::new(Node {
+                        value: Expression::List(vec![
+                            Node {
+                                value: Expression::Integer(1),
+                                position: position(9),
+                            },
+                            Node {
+                                value: Expression::Integer(2),
+                                position: position(12),
+                            },
+                        ]),
+                        position: position(8),
+                    }),
+                    variant: AssignmentVariant::Base,
+                },
+                position: position(0),
+            }]
+        );
+    }
+
+    #[test]
+    fn parse_unterminated_list() {
+        let input = String::from("let xs = [1, 2;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        assert_eq!(
+            statements[0],
+            Node {
+                value: Statement::Error("expected 'RBracket', got 'Semicolon'".to_string()),
+                position: Position {
+                    index: 14,
+                    line: 1,
+                    column: 15
+                }
+            }
+        );
+    }
+
+    #[test]
+    fn parse_map_and_member() {
+        let input = String::from("let m = { \"a\": 1, \"b\": {:}, }; m.a;");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let position = |index: usize| Position {
+            index,
+            line: 1,
+            column: index + 1,
+        };
+        assert_eq!(
+            statements,
+            vec![
+                Node {
+                    value: Statement::Let {
+                        mutable: false,
+                        identifier: Box::new(Node {
+                            value: Expression::Identifier("m".to_string()),
+                            position: position(4),
+                        }),
+                        annotation: None,
+                        value: Box::new(Node {
+                            value: Expression::Map(vec![
+                                (
+                                    Node {
+                                        value: Expression::String("a".to_string()),
+                                        position: position(10),
+                                    },
+                                    Node {
+                                        value: Expression::Integer(1),
+                                        position: position(15),
+                                    },
+                                ),
+                                (
+                                    Node {
+                                        value: Expression::String("b".to_string()),
+                                        position: position(18),
+                                    },
+                                    Node {
+                                        value: Expression::Map(vec![]),
+                                        position: position(23),
+                                    },
+                                ),
+                            ]),
+                            position: position(8),
+                        }),
+                    },
+                    position: position(0),
+                },
+                Node {
+                    value: Statement::Expression(Node {
+                        value: Expression::Member {
+                            subject: Box::new(Node {
+                                value: Expression::Identifier("m".to_string()),
+                                position: position(31),
+                            }),
+                            member: Box::new(Node {
+                                value: Expression::Identifier("a".to_string()),
+                                position: position(33),
+                            }),
+                        },
+                        position: position(31),
+                    }),
+                    position: position(31),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn block_starting_with_string_is_not_a_map() {
+        let input = String::from("let a = { \"a\"; 1 + 1; };");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let [Node {
+            value: Statement::Let { value, .. },
+            ..
+        }] = statements.as_slice()
+        else {
+            panic!("expected a single Let, got {statements:#?}");
+        };
+        assert!(
+            matches!(&value.value, Expression::Block(statements) if statements.len() == 2),
+            "{value:#?}"
+        );
+    }
+
+    #[test]
+    fn parse_struct_declaration_and_literal() {
+        let input = String::from("struct P { x, y, } P { x: 1, y: a };");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let identifier = |name: &str, index| node(Expression::Identifier(name.to_string()), index);
+        assert_eq!(
+            statements,
+            vec![
+                node(
+                    Statement::Struct {
+                        identifier: Box::new(identifier("P", 7)),
+                        fields: vec![identifier("x", 11), identifier("y", 14)],
+                    },
+                    0
+                ),
+                node(
+                    Statement::Expression(node(
+                        Expression::Struct {
+                            identifier: Box::new(identifier("P", 19)),
+                            fields: vec![
+                                (identifier("x", 23), node(Expression::Integer(1), 26)),
+                                (identifier("y", 29), identifier("a", 32)),
+                            ],
+                        },
+                        19
+                    )),
+                    19
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn struct_literal_not_allowed_in_condition() {
+        let input = String::from("if p { 1; } while (P {}).x { 2; }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let [Node {
+            value: Statement::Expression(if_expression),
+            ..
+        }, Node {
+            value: Statement::While { condition, .. },
+            ..
+        }] = statements.as_slice()
+        else {
+            panic!("expected If and While, got {statements:#?}");
+        };
+        assert!(
+            matches!(&if_expression.value, Expression::If { condition, .. } if condition.value == Expression::Identifier("p".to_string())),
+            "{if_expression:#?}"
+        );
+        assert!(
+            matches!(&condition.value, Expression::Member { subject, .. } if matches!(subject.value, Expression::Struct { .. })),
+            "{condition:#?}"
+        );
+    }
+
+    #[test]
+    fn parse_impl() {
+        let input = String::from("impl P { fn a(self) {} fn b(self, c) { return c; } }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let [Node {
+            value:
+                Statement::Impl {
+                    identifier,
+                    methods,
+                },
+            ..
+        }] = statements.as_slice()
+        else {
+            panic!("expected a single Impl, got {statements:#?}");
+        };
+        assert_eq!(identifier.value, Expression::Identifier("P".to_string()));
+        assert_eq!(methods.len(), 2);
+        assert!(methods
+            .iter()
+            .all(|method| matches!(method.value, Statement::Function { .. })));
+
+        let input = String::from("impl P { let a = 1; }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        assert_eq!(
+            statements[0].value,
+            Statement::Error("expected 'FnKeyword', got 'LetKeyword'".to_string())
+        );
+    }
+
+    #[test]
+    fn parse_enum_and_path() {
+        let input = String::from("enum S { A, B(x, y), } S::B(1, 2);");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let identifier = |name: &str, index| node(Expression::Identifier(name.to_string()), index);
+        assert_eq!(
+            statements,
+            vec![
+                node(
+                    Statement::Enum {
+                        identifier: Box::new(identifier("S", 5)),
+                        variants: vec![
+                            (identifier("A", 9), vec![]),
+                            (
+                                identifier("B", 12),
+                                vec![identifier("x", 14), identifier("y", 17)]
+                            ),
+                        ],
+                    },
+                    0
+                ),
+                node(
+                    Statement::Expression(node(
+                        Expression::Call {
+                            subject: Box::new(node(
+                                Expression::Path {
+                                    subject: Box::new(identifier("S", 23)),
+                                    member: Box::new(identifier("B", 26)),
+                                },
+                                23
+                            )),
+                            arguments: vec![
+                                node(Expression::Integer(1), 28),
+                                node(Expression::Integer(2), 31),
+                            ],
+                        },
+                        23
+                    )),
+                    23
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_match() {
+        let input = String::from("match x { S::B(_, -1) if y => 1, \"a\" => { 2; } n => n }");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let identifier = |name: &str, index| node(Expression::Identifier(name.to_string()), index);
+        let [Node {
+            value:
+                Statement::Expression(Node {
+                    value: Expression::Match { subject, arms },
+                    ..
+                }),
+            ..
+        }] = statements.as_slice()
+        else {
+            panic!("expected a single Match, got {statements:#?}");
+        };
+        assert_eq!(**subject, identifier("x", 6));
+        assert_eq!(
+            *arms,
+            vec![
+                MatchArm {
+                    pattern: node(
+                        Pattern::Variant {
+                            enumeration: Box::new(identifier("S", 10)),
+                            variant: Box::new(identifier("B", 13)),
+                            fields: vec![
+                                node(Pattern::Wildcard, 15),
+                                node(Pattern::Literal(Expression::Integer(-1)), 18),
+                            ],
+                        },
+                        10
+                    ),
+                    guard: Some(identifier("y", 25)),
+                    body: node(Expression::Integer(1), 30),
+                },
+                MatchArm {
+                    pattern: node(Pattern::Literal(Expression::String("a".to_string())), 33),
+                    guard: None,
+                    body: node(
+                        Expression::Block(vec![node(
+                            Statement::Expression(node(Expression::Integer(2), 42)),
+                            42
+                        )]),
+                        40
+                    ),
+                },
+                MatchArm {
+                    pattern: node(Pattern::Identifier("n".to_string()), 47),
+                    guard: None,
+                    body: identifier("n", 52),
+                },
+            ]
+        );
+
+        let cases = [
+            (
+                "match x { 1 => 2 3 => 4 }",
+                "expected 'RBrace', got 'Integer'",
+            ),
+            ("match x { 1 2 }", "expected 'EqualGreater', got 'Integer'"),
+            ("match x { + => 1 }", "unexpected pattern Plus"),
+            (
+                "match x { S::A(1 => 1 }",
+                "expected 'RParenthesis', got 'EqualGreater'",
+            ),
+        ];
+        for (input, message) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statement = parser.parse_statement();
+            assert_eq!(
+                statement.value,
+                Statement::Error(message.to_string()),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn parse_type_annotations() {
+        let input =
+            String::from("let x: [int] = []; fn f(a: {float}, g: fn(int) -> bool) -> fn() {}");
+        let lexer = Lexer::new(input.chars());
+        let mut parser = Parser::new(lexer.into_iter(), input.clone());
+        let statements = parser.parse_statements();
+        let named = |name: &str, index| node(TypeAnnotation::Named(name.to_string()), index);
+        let [Node {
+            value: Statement::Let { annotation, .. },
+            ..
+        }, Node {
+            value:
+                Statement::Function {
+                    parameters,
+                    return_type,
+                    ..
+                },
+            ..
+        }] = statements.as_slice()
+        else {
+            panic!("expected Let and Function, got {statements:#?}");
+        };
+        assert_eq!(
+            *annotation,
+            Some(Box::new(node(
+                TypeAnnotation::List(Box::new(named("int", 8))),
+                7
+            )))
+        );
+        let annotations: Vec<_> = parameters
+            .iter()
+            .map(|parameter| match &parameter.value {
+                Parameter::Item { annotation, .. } => annotation.clone(),
+                Parameter::Error(message) => panic!("unexpected error {message}"),
+            })
+            .collect();
+        assert_eq!(
+            annotations,
+            vec![
+                Some(Box::new(node(
+                    TypeAnnotation::Map(Box::new(named("float", 28))),
+                    27
+                ))),
+                Some(Box::new(node(
+                    TypeAnnotation::Function {
+                        parameters: vec![named("int", 42)],
+                        return_type: Box::new(named("bool", 50)),
+                    },
+                    39
+                ))),
+            ]
+        );
+        assert_eq!(
+            *return_type,
+            Some(Box::new(node(
+                TypeAnnotation::Function {
+                    parameters: vec![],
+                    return_type: Box::new(named("unit", 59)),
+                },
+                59
+            )))
+        );
+
+        let cases = [
+            ("let x: = 1;", "unexpected type Equal"),
+            ("let x: [int = 1;", "expected 'RBracket', got 'Equal'"),
+            ("fn f() -> {}", "unexpected type RBrace"),
+        ];
+        for (input, message) in cases {
+            let input = String::from(input);
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            let statement = parser.parse_statement();
+            assert_eq!(
+                statement.value,
+                Statement::Error(message.to_string()),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn nesting_depth_is_limited() {
+        let message = format!("maximum nesting depth of {MAX_NESTING_DEPTH} exceeded");
+        let parse = |input: String| {
+            let lexer = Lexer::new(input.chars());
+            let mut parser = Parser::new(lexer.into_iter(), input.clone());
+            parser.parse_statements()
+        };
+        let last = |index| Node {
+            value: Statement::Expression(Node {
+                value: Expression::Integer(2),
+                position: Position {
+                    index,
+                    line: 2,
+                    column: 1,
+                },
+            }),
+            position: Position {
+                index,
+                line: 2,
+                column: 1,
+            },
+        };
+
+        let depth = 5000;
+        let input = format!("let a = {}1{};\n2;", "(".repeat(depth), ")".repeat(depth));
+        let statements = parse(input);
+        assert_eq!(
+            statements,
+            vec![
+                Node {
+                    value: Statement::Error(message.clone()),
+                    position: Position {
+                        index: 8 + MAX_NESTING_DEPTH - 1,
+                        line: 1,
+                        column: 8 + MAX_NESTING_DEPTH,
+                    },
+                },
+                last(8 + depth * 2 + 3),
+            ]
+        );
+
+        let input = format!("{}1;{}\n2;", "{".repeat(depth), "}".repeat(depth));
+        let statements = parse(input);
+        assert_eq!(statements[1], last(depth * 2 + 3));
+        let mut statement = &statements[0];
+        let mut blocks = 0;
+        while let Statement::Expression(Node {
+            value: Expression::Block(statements),
+            ..
+        }) = &statement.value
+        {
+            assert_eq!(statements.len(), 1);
+            statement = &statements[0];
+            blocks += 1;
+        }
+        assert_eq!(blocks, MAX_NESTING_DEPTH);
+        assert_eq!(statement.value, Statement::Error(message.clone()));
+
+        let input = format!("{}1;\n2;", "-".repeat(depth));
+        let statements = parse(input);
+        assert_eq!(statements.len(), 2);
+        assert_eq!(statements[1], last(depth + 3));
+    }
 }
@@ -3,11 +3,18 @@ use std::{
     io::{stdin, stdout, BufRead, Write},
 };
 
-use couch_lang_evaluator::Evaluator;
+use couch_lang_evaluator::{Evaluator, IdentifierType};
 use couch_lang_lexer::{Lexer, Token};
 use couch_lang_parser::Parser;
+use couch_lang_typeck::TypeChecker;
 
-fn eval(code: String, print_tokens: bool, print_ast: bool) {
+fn eval(
+    evaluator: &mut Evaluator,
+    checker: &mut TypeChecker,
+    code: String,
+    print_tokens: bool,
+    print_ast: bool,
+) {
     let lexer = Lexer::new(code.chars());
     let tokens: Vec<Token> = lexer.into_iter().collect();
     if print_tokens {
@@ -26,8 +33,38 @@ fn eval(code: String, print_tokens: bool, print_ast: bool) {
         }
         println!("]");
     }
-    let value = Evaluator::evaluate_statements(ast);
-    println!("value -> {value:?}");
+    let checkpoint = checker.clone();
+    for warning in checker.check(&ast).iter() {
+        println!("warning -> {warning}");
+    }
+    let outcome = evaluator.run(&ast);
+    if !outcome.diagnostics.is_empty() {
+        *checker = checkpoint;
+    }
+    for diagnostic in outcome.diagnostics.iter() {
+        println!("error -> {diagnostic}");
+    }
+    if outcome.exit_code != 0 {
+        println!("exit code -> {}", outcome.exit_code);
+    }
+    println!("value -> {:?}", outcome.value);
+}
+
+fn print_env(evaluator: &Evaluator) {
+    println!("env -> [");
+    for (name, definition) in evaluator.definitions() {
+        match definition {
+            IdentifierType::Value {
+                mutable: true,
+                value,
+            } => println!(" mut {name} -> {value:?}"),
+            IdentifierType::Value { value, .. } => println!(" {name} -> {value:?}"),
+            IdentifierType::Function { .. } => println!(" {name} -> function"),
+            IdentifierType::Struct { .. } => println!(" {name} -> struct"),
+            IdentifierType::Enum { .. } => println!(" {name} -> enum"),
+        }
+    }
+    println!("]");
 }
 
 fn print_help() {
@@ -41,11 +78,15 @@ fn print_help() {
     println!(":exit --> exit the program");
     println!(":eval --> (multiline) evaluate code in program buffer");
     println!(":show --> (multiline) show current program buffer");
+    println!(":env --> list definitions kept between evaluations");
+    println!(":reset --> forget all definitions");
     println!();
 }
 
 fn main() -> ! {
     let mut code_buffer = String::new();
+    let mut evaluator = Evaluator::new();
+    let mut checker = TypeChecker::new();
     let print_tokens = env::args().find(|s| s == "--tokens" || s == "-t").is_some();
     let print_ast = env::args().find(|s| s == "--ast" || s == "-a").is_some();
     let multiline = env::args().find(|s| s == "--multi" || s == "-m").is_some();
@@ -61,13 +102,30 @@ fn main() -> ! {
         stdin.read_line(&mut line_buffer).unwrap();
         if line_buffer.trim() == ":exit" {
             std::process::exit(0)
+        } else if line_buffer.trim() == ":env" {
+            print_env(&evaluator);
+        } else if line_buffer.trim() == ":reset" {
+            evaluator.reset();
+            checker = TypeChecker::new();
         } else if multiline && line_buffer.trim() == ":show" {
             print!("{code_buffer}");
         } else if multiline && line_buffer.trim() == ":eval" {
-            eval(code_buffer.clone(), print_tokens, print_ast);
+            eval(
+                &mut evaluator,
+                &mut checker,
+                code_buffer.clone(),
+                print_tokens,
+                print_ast,
+            );
             code_buffer = "".to_owned();
         } else if !multiline {
-            eval(line_buffer, print_tokens, print_ast);
+            eval(
+                &mut evaluator,
+                &mut checker,
+                line_buffer,
+                print_tokens,
+                print_ast,
+            );
         } else {
             code_buffer += &line_buffer;
         }
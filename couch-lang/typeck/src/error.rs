@@ -0,0 +1,16 @@
+use std::fmt::Display;
+
+use couch_lang_parser::Position;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct TypeError {
+    pub message: String,
+    pub position: Position,
+}
+
+impl Display for TypeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Position { line, column, .. } = self.position;
+        write!(f, "type error at {line}:{column}: {}", self.message)
+    }
+}
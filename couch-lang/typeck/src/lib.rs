@@ -0,0 +1,1462 @@
+use std::collections::HashMap;
+
+use couch_lang_parser::{
+    AssignmentVariant, BinaryVariant, Expression, MatchArm, Node, Parameter, Pattern, Position,
+    Statement, TypeAnnotation, UnaryVariant,
+};
+
+pub mod error;
+mod prelude;
+pub mod types;
+use error::TypeError;
+use types::{Scheme, Type};
+
+#[derive(Debug, Clone)]
+struct StructInfo {
+    fields: Vec<String>,
+    methods: HashMap<String, Scheme>,
+}
+
+#[derive(Debug, Clone)]
+struct EnumInfo {
+    parameters: usize,
+    variants: Vec<(String, Vec<usize>)>,
+}
+
+#[derive(Clone)]
+enum Requirement {
+    Operator(String),
+    Number,
+}
+
+#[derive(Clone)]
+struct Constraint {
+    value: Type,
+    allowed: &'static [Type],
+    requirement: Requirement,
+    position: Position,
+}
+
+/// Unannotated functions returning values of different types return `any`.
+#[derive(Clone)]
+struct Return {
+    value: Type,
+    annotated: bool,
+    mixed: bool,
+}
+
+const NUMBERS: &[Type] = &[Type::Integer, Type::Float];
+const ADDABLE: &[Type] = &[Type::Integer, Type::Float, Type::String];
+
+fn operator(variant: &BinaryVariant) -> &'static str {
+    match variant {
+        BinaryVariant::Addition => "+",
+        BinaryVariant::Subtraction => "-",
+        BinaryVariant::Multiplication => "*",
+        BinaryVariant::Division => "/",
+        BinaryVariant::Equal => "==",
+        BinaryVariant::NotEqual => "!=",
+        BinaryVariant::Less => "<",
+        BinaryVariant::LessEqual => "<=",
+        BinaryVariant::Greater => ">",
+        BinaryVariant::GreaterEqual => ">=",
+        BinaryVariant::And => "&&",
+        BinaryVariant::Or => "||",
+        BinaryVariant::Modulo => "%",
+        BinaryVariant::Exponentiation => "**",
+        BinaryVariant::BitwiseAnd => "&",
+        BinaryVariant::BitwiseOr => "|",
+        BinaryVariant::BitwiseXor => "^",
+        BinaryVariant::LeftShift => "<<",
+        BinaryVariant::RightShift => ">>",
+    }
+}
+
+fn assignment_operator(variant: &AssignmentVariant) -> (&'static str, &'static [Type]) {
+    match variant {
+        AssignmentVariant::Base => ("=", &[]),
+        AssignmentVariant::Addition => ("+=", ADDABLE),
+        AssignmentVariant::Subtraction => ("-=", NUMBERS),
+        AssignmentVariant::Multiplication => ("*=", NUMBERS),
+        AssignmentVariant::Division => ("/=", NUMBERS),
+        AssignmentVariant::Modulo => ("%=", NUMBERS),
+    }
+}
+
+fn always_returns(statements: &[Node<Statement>]) -> bool {
+    let Some(last) = statements.last() else {
+        return false;
+    };
+    match &last.value {
+        Statement::Return(_) => true,
+        Statement::Expression(expression) => expression_returns(expression),
+        _ => false,
+    }
+}
+
+fn expression_returns(expression: &Node<Expression>) -> bool {
+    match &expression.value {
+        Expression::Block(statements) => always_returns(statements),
+        Expression::If {
+            truthy,
+            falsy: Some(falsy),
+            ..
+        } => expression_returns(truthy) && expression_returns(falsy),
+        Expression::Match { arms, .. } => {
+            !arms.is_empty() && arms.iter().all(|arm| expression_returns(&arm.body))
+        }
+        _ => false,
+    }
+}
+
+pub fn check(statements: &[Node<Statement>]) -> Vec<TypeError> {
+    TypeChecker::new().check(statements)
+}
+
+#[derive(Clone)]
+pub struct TypeChecker {
+    substitution: Vec<Option<Type>>,
+    scopes: Vec<HashMap<String, Scheme>>,
+    structs: HashMap<String, StructInfo>,
+    enums: HashMap<String, EnumInfo>,
+    return_types: Vec<Return>,
+    constraints: Vec<Constraint>,
+    numeric: Vec<usize>,
+    errors: Vec<TypeError>,
+}
+
+impl Default for TypeChecker {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TypeChecker {
+    pub fn new() -> Self {
+        let mut checker = Self {
+            substitution: Vec::new(),
+            scopes: vec![HashMap::new()],
+            structs: HashMap::new(),
+            enums: HashMap::new(),
+            return_types: Vec::new(),
+            constraints: Vec::new(),
+            numeric: Vec::new(),
+            errors: Vec::new(),
+        };
+        prelude::register_prelude(&mut checker);
+        checker.scopes.push(HashMap::new());
+        checker
+    }
+    pub fn check(&mut self, statements: &[Node<Statement>]) -> Vec<TypeError> {
+        let scopes = self.scopes.clone();
+        let structs = self.structs.clone();
+        let enums = self.enums.clone();
+
+        for statement in statements {
+            self.check_statement(statement);
+        }
+        for constraint in std::mem::take(&mut self.constraints) {
+            self.check_constraint(constraint);
+        }
+
+        let mut errors = std::mem::take(&mut self.errors);
+        errors.sort_by_key(|error| error.position.index);
+        if !errors.is_empty() {
+            self.scopes = scopes;
+            self.structs = structs;
+            self.enums = enums;
+        }
+        errors
+    }
+    pub fn type_of(&self, name: &str) -> Option<Type> {
+        self.lookup(name).map(|scheme| self.resolve(&scheme.value))
+    }
+    pub(crate) fn register(&mut self, name: &str, parameters: Vec<Type>, result: Type) {
+        let value = Type::Function(parameters, Box::new(result));
+        let scheme = self.generalize(&value);
+        self.define(name, scheme);
+    }
+    pub(crate) fn fresh(&mut self) -> Type {
+        self.substitution.push(None);
+        Type::Variable(self.substitution.len() - 1)
+    }
+    pub(crate) fn number(&mut self) -> Type {
+        self.numeric.push(self.substitution.len());
+        self.fresh()
+    }
+    fn error(&mut self, message: String, position: &Position) {
+        self.errors.push(TypeError {
+            message,
+            position: position.clone(),
+        });
+    }
+    fn define(&mut self, name: &str, scheme: Scheme) {
+        self.scopes
+            .last_mut()
+            .expect("there is always a scope")
+            .insert(name.to_string(), scheme);
+    }
+    fn lookup(&self, name: &str) -> Option<&Scheme> {
+        self.scopes.iter().rev().find_map(|scope| scope.get(name))
+    }
+    fn shallow(&self, value: &Type) -> Type {
+        let mut value = value.clone();
+        while let Type::Variable(variable) = value {
+            match &self.substitution[variable] {
+                Some(bound) => value = bound.clone(),
+                None => break,
+            }
+        }
+        value
+    }
+    fn resolve(&self, value: &Type) -> Type {
+        match self.shallow(value) {
+            Type::List(element) => Type::List(Box::new(self.resolve(&element))),
+            Type::Map(element) => Type::Map(Box::new(self.resolve(&element))),
+            Type::Function(parameters, result) => Type::Function(
+                parameters.iter().map(|value| self.resolve(value)).collect(),
+                Box::new(self.resolve(&result)),
+            ),
+            Type::Struct(name, parameters) => Type::Struct(
+                name,
+                parameters.iter().map(|value| self.resolve(value)).collect(),
+            ),
+            Type::Enum(name, parameters) => Type::Enum(
+                name,
+                parameters.iter().map(|value| self.resolve(value)).collect(),
+            ),
+            value => value,
+        }
+    }
+    fn unify(&mut self, left: &Type, right: &Type) -> Result<(), (Type, Type)> {
+        let left = self.shallow(left);
+        let right = self.shallow(right);
+        match (&left, &right) {
+            (Type::Any, _) | (_, Type::Any) => Ok(()),
+            (Type::Variable(a), Type::Variable(b)) if a == b => Ok(()),
+            (Type::Variable(variable), value) | (value, Type::Variable(variable)) => {
+                let mut variables = Vec::new();
+                self.resolve(value).variables(&mut variables);
+                if variables.contains(variable) {
+                    return Err((left.clone(), right.clone()));
+                }
+                self.substitution[*variable] = Some(value.clone());
+                Ok(())
+            }
+            (Type::List(a), Type::List(b)) | (Type::Map(a), Type::Map(b)) => self.unify(a, b),
+            (Type::Function(a, a_result), Type::Function(b, b_result)) if a.len() == b.len() => {
+                for (a, b) in a.iter().zip(b) {
+                    self.unify(a, b)?;
+                }
+                self.unify(a_result, b_result)
+            }
+            (Type::Struct(a_name, a), Type::Struct(b_name, b))
+            | (Type::Enum(a_name, a), Type::Enum(b_name, b))
+                if a_name == b_name && a.len() == b.len() =>
+            {
+                for (a, b) in a.iter().zip(b) {
+                    self.unify(a, b)?;
+                }
+                Ok(())
+            }
+            (a, b) if a == b => Ok(()),
+            _ => Err((left, right)),
+        }
+    }
+    fn expect(&mut self, expected: &Type, actual: &Type, position: &Position) -> bool {
+        let Err((inner_expected, inner_actual)) = self.unify(expected, actual) else {
+            return true;
+        };
+        let (mut expected, mut actual) = (self.resolve(expected), self.resolve(actual));
+        if expected.to_string() == actual.to_string() {
+            expected = self.resolve(&inner_expected);
+            actual = self.resolve(&inner_actual);
+        }
+        self.error(format!("expected {expected}, got {actual}"), position);
+        false
+    }
+    fn common(&mut self, values: &[Type]) -> Type {
+        let result = self.fresh();
+        for value in values {
+            if self.unify(&result, value).is_err() {
+                return Type::Any;
+            }
+        }
+        result
+    }
+    fn free_in_scopes(&self) -> Vec<usize> {
+        let mut variables = Vec::new();
+        for scope in &self.scopes {
+            for scheme in scope.values() {
+                let mut free = Vec::new();
+                self.resolve(&scheme.value).variables(&mut free);
+                variables.extend(
+                    free.into_iter()
+                        .filter(|variable| !scheme.variables.contains(variable)),
+                );
+            }
+        }
+        for Return { value, .. } in &self.return_types {
+            self.resolve(value).variables(&mut variables);
+        }
+        variables
+    }
+    fn generalize(&self, value: &Type) -> Scheme {
+        let value = self.resolve(value);
+        let mut variables = Vec::new();
+        value.variables(&mut variables);
+        let free = self.free_in_scopes();
+        variables.retain(|variable| !free.contains(variable));
+        let numeric = variables
+            .iter()
+            .filter(|variable| self.numeric.contains(variable))
+            .copied()
+            .collect();
+        Scheme {
+            variables,
+            numeric,
+            value,
+        }
+    }
+    fn instantiate(&mut self, scheme: &Scheme, position: &Position) -> Type {
+        let replacements: HashMap<usize, Type> = scheme
+            .variables
+            .iter()
+            .map(|variable| (*variable, self.fresh()))
+            .collect();
+        for variable in &scheme.numeric {
+            let value = replacements[variable].clone();
+            self.require(&value, NUMBERS, Requirement::Number, position);
+        }
+        Self::substitute(&self.resolve(&scheme.value), &replacements)
+    }
+    fn substitute(value: &Type, replacements: &HashMap<usize, Type>) -> Type {
+        let substitute = |value: &Type| Self::substitute(value, replacements);
+        match value {
+            Type::Variable(variable) => replacements
+                .get(variable)
+                .cloned()
+                .unwrap_or(Type::Variable(*variable)),
+            Type::List(element) => Type::List(Box::new(substitute(element))),
+            Type::Map(element) => Type::Map(Box::new(substitute(element))),
+            Type::Function(parameters, result) => Type::Function(
+                parameters.iter().map(substitute).collect(),
+                Box::new(substitute(result)),
+            ),
+            Type::Struct(name, parameters) => {
+                Type::Struct(name.clone(), parameters.iter().map(substitute).collect())
+            }
+            Type::Enum(name, parameters) => {
+                Type::Enum(name.clone(), parameters.iter().map(substitute).collect())
+            }
+            value => value.clone(),
+        }
+    }
+    fn require(
+        &mut self,
+        value: &Type,
+        allowed: &'static [Type],
+        requirement: Requirement,
+        position: &Position,
+    ) {
+        self.constraints.push(Constraint {
+            value: value.clone(),
+            allowed,
+            requirement,
+            position: position.clone(),
+        });
+    }
+    fn check_constraint(&mut self, constraint: Constraint) {
+        let value = self.resolve(&constraint.value);
+        if matches!(value, Type::Variable(_) | Type::Any) || constraint.allowed.contains(&value) {
+            return;
+        }
+        let message = match constraint.requirement {
+            Requirement::Operator(operator) => {
+                format!("no implementation exists for {value} {operator} {value}")
+            }
+            Requirement::Number => format!("expected number, got {value}"),
+        };
+        self.error(message, &constraint.position);
+    }
+    fn struct_type(&mut self, name: &str) -> Option<Type> {
+        let fields = self.structs.get(name)?.fields.len();
+        let parameters = (0..fields).map(|_| self.fresh()).collect();
+        Some(Type::Struct(name.to_string(), parameters))
+    }
+    fn enum_type(&mut self, name: &str) -> Option<Type> {
+        let count = self.enums.get(name)?.parameters;
+        let parameters = (0..count).map(|_| self.fresh()).collect();
+        Some(Type::Enum(name.to_string(), parameters))
+    }
+    fn annotation(&mut self, node: &Node<TypeAnnotation>) -> Type {
+        match &node.value {
+            TypeAnnotation::Named(name) => match name.as_str() {
+                "int" | "integer" => Type::Integer,
+                "float" => Type::Float,
+                "bool" => Type::Bool,
+                "string" => Type::String,
+                "unit" => Type::Unit,
+                "any" => Type::Any,
+                name => match self.struct_type(name).or_else(|| self.enum_type(name)) {
+                    Some(value) => value,
+                    None => {
+                        self.error(format!("unknown type {name}"), &node.position);
+                        Type::Any
+                    }
+                },
+            },
+            TypeAnnotation::List(element) => Type::List(Box::new(self.annotation(element))),
+            TypeAnnotation::Map(element) => Type::Map(Box::new(self.annotation(element))),
+            TypeAnnotation::Function {
+                parameters,
+                return_type,
+            } => Type::Function(
+                parameters
+                    .iter()
+                    .map(|parameter| self.annotation(parameter))
+                    .collect(),
+                Box::new(self.annotation(return_type)),
+            ),
+            TypeAnnotation::Error(_) => Type::Any,
+        }
+    }
+    fn check_statement(&mut self, node: &Node<Statement>) -> Type {
+        match &node.value {
+            Statement::Function {
+                identifier,
+                parameters,
+                return_type,
+                body,
+            } => {
+                let Expression::Identifier(name) = &identifier.value else {
+                    return Type::Unit;
+                };
+                let recursive = self.fresh();
+                self.scopes.push(HashMap::new());
+                self.define(name, Scheme::mono(recursive.clone()));
+                let function = self.check_function(
+                    name,
+                    parameters,
+                    return_type.as_deref(),
+                    body,
+                    None,
+                    &node.position,
+                );
+                self.expect(&recursive, &function, &node.position);
+                self.scopes.pop();
+                let scheme = self.generalize(&function);
+                self.define(name, scheme);
+            }
+            Statement::Let {
+                identifier,
+                annotation,
+                value,
+                ..
+            } => {
+                let mut value_type = self.infer(value);
+                if let Some(annotation) = annotation {
+                    let annotation = self.annotation(annotation);
+                    self.expect(&annotation, &value_type, &value.position);
+                    value_type = annotation;
+                }
+                let scheme = match value.value {
+                    Expression::Function { .. }
+                    | Expression::Identifier(_)
+                    | Expression::Path { .. } => self.generalize(&value_type),
+                    _ => Scheme::mono(value_type),
+                };
+                if let Expression::Identifier(name) = &identifier.value {
+                    self.define(name, scheme);
+                }
+            }
+            Statement::Return(value) => {
+                let (value_type, position) = match value {
+                    Some(value) => (self.infer(value), &value.position),
+                    None => (Type::Unit, &node.position),
+                };
+                let Some(expected) = self.return_types.last().cloned() else {
+                    return Type::Unit;
+                };
+                if expected.annotated {
+                    self.expect(&expected.value, &value_type, position);
+                } else if self.unify(&expected.value, &value_type).is_err() {
+                    self.return_types.last_mut().expect("checked above").mixed = true;
+                }
+            }
+            Statement::While { condition, body } => {
+                let condition_type = self.infer(condition);
+                self.expect(&Type::Bool, &condition_type, &condition.position);
+                self.infer(body);
+            }
+            Statement::Break | Statement::Continue | Statement::Error(_) => {}
+            Statement::Struct { identifier, fields } => {
+                if let Expression::Identifier(name) = &identifier.value {
+                    let fields = fields
+                        .iter()
+                        .filter_map(|field| match &field.value {
+                            Expression::Identifier(field) => Some(field.clone()),
+                            _ => None,
+                        })
+                        .collect();
+                    let info = StructInfo {
+                        fields,
+                        methods: HashMap::new(),
+                    };
+                    self.structs.insert(name.clone(), info);
+                }
+            }
+            Statement::Impl {
+                identifier,
+                methods,
+            } => self.check_impl(identifier, methods),
+            Statement::Enum {
+                identifier,
+                variants,
+            } => {
+                if let Expression::Identifier(name) = &identifier.value {
+                    let mut parameters = 0;
+                    let variants = variants
+                        .iter()
+                        .filter_map(|(variant, fields)| match &variant.value {
+                            Expression::Identifier(variant) => {
+                                let indices = (parameters..parameters + fields.len()).collect();
+                                parameters += fields.len();
+                                Some((variant.clone(), indices))
+                            }
+                            _ => None,
+                        })
+                        .collect();
+                    let info = EnumInfo {
+                        parameters,
+                        variants,
+                    };
+                    self.enums.insert(name.clone(), info);
+                }
+            }
+            Statement::Assignment {
+                left,
+                right,
+                variant,
+            } => {
+                let left_type = self.infer(left);
+                let right_type = self.infer(right);
+                let (operator, allowed) = assignment_operator(variant);
+                if *variant == AssignmentVariant::Base {
+                    self.expect(&left_type, &right_type, &right.position);
+                } else if self.unify(&left_type, &right_type).is_ok() {
+                    let requirement = Requirement::Operator(operator.to_string());
+                    self.require(&left_type, allowed, requirement, &node.position);
+                } else {
+                    let (left_type, right_type) =
+                        (self.resolve(&left_type), self.resolve(&right_type));
+                    self.error(
+                        format!("no implementation exists for {left_type} {operator} {right_type}"),
+                        &node.position,
+                    );
+                }
+            }
+            Statement::Expression(expression) => return self.infer(expression),
+        }
+        Type::Unit
+    }
+    fn check_impl(&mut self, identifier: &Node<Expression>, methods: &[Node<Statement>]) {
+        let Expression::Identifier(name) = &identifier.value else {
+            return;
+        };
+        if !self.structs.contains_key(name) {
+            return;
+        }
+        let mut placeholders = Vec::new();
+        for method in methods {
+            if let Statement::Function { identifier, .. } = &method.value {
+                if let Expression::Identifier(method) = &identifier.value {
+                    let placeholder = self.fresh();
+                    self.structs
+                        .get_mut(name)
+                        .expect("checked above")
+                        .methods
+                        .insert(method.clone(), Scheme::mono(placeholder.clone()));
+                    placeholders.push(placeholder);
+                    continue;
+                }
+            }
+            placeholders.push(Type::Any);
+        }
+        for (method, placeholder) in methods.iter().zip(placeholders) {
+            let Statement::Function {
+                identifier,
+                parameters,
+                return_type,
+                body,
+            } = &method.value
+            else {
+                continue;
+            };
+            let Expression::Identifier(method_name) = &identifier.value else {
+                continue;
+            };
+            let receiver = self.struct_type(name).expect("checked above");
+            let function = self.check_function(
+                &format!("{name}.{method_name}"),
+                parameters,
+                return_type.as_deref(),
+                body,
+                Some(receiver),
+                &method.position,
+            );
+            self.expect(&placeholder, &function, &method.position);
+            let scheme = self.generalize(&function);
+            self.structs
+                .get_mut(name)
+                .expect("checked above")
+                .methods
+                .insert(method_name.clone(), scheme);
+        }
+    }
+    fn check_function(
+        &mut self,
+        name: &str,
+        parameters: &[Node<Parameter>],
+        return_type: Option<&Node<TypeAnnotation>>,
+        body: &Node<Expression>,
+        receiver: Option<Type>,
+        position: &Position,
+    ) -> Type {
+        self.scopes.push(HashMap::new());
+        let mut parameter_types = Vec::new();
+        for (index, parameter) in parameters.iter().enumerate() {
+            let Parameter::Item {
+                identifier,
+                annotation,
+                ..
+            } = &parameter.value
+            else {
+                parameter_types.push(Type::Any);
+                continue;
+            };
+            let mut parameter_type = match annotation {
+                Some(annotation) => self.annotation(annotation),
+                None => self.fresh(),
+            };
+            if let (0, Some(receiver)) = (index, &receiver) {
+                self.expect(receiver, &parameter_type, &parameter.position);
+                parameter_type = receiver.clone();
+            }
+            if let Expression::Identifier(name) = &identifier.value {
+                self.define(name, Scheme::mono(parameter_type.clone()));
+            }
+            parameter_types.push(parameter_type);
+        }
+        let result = match return_type {
+            Some(annotation) => self.annotation(annotation),
+            None => self.fresh(),
+        };
+
+        self.return_types.push(Return {
+            value: result.clone(),
+            annotated: return_type.is_some(),
+            mixed: false,
+        });
+        self.infer(body);
+        let Return { mixed, .. } = self.return_types.pop().expect("pushed above");
+        self.scopes.pop();
+
+        let returns = match &body.value {
+            Expression::Block(statements) => always_returns(statements),
+            _ => false,
+        };
+        if mixed || (!returns && self.unify(&result, &Type::Unit).is_err()) {
+            if return_type.is_none() {
+                return Type::Function(parameter_types, Box::new(Type::Any));
+            }
+            let result = self.resolve(&result);
+            self.error(
+                format!("function {name} must return {result} on every path"),
+                position,
+            );
+        }
+        Type::Function(parameter_types, Box::new(result))
+    }
+    fn check_pattern(&mut self, node: &Node<Pattern>, expected: &Type) {
+        match &node.value {
+            Pattern::Wildcard | Pattern::Error(_) => {}
+            Pattern::Identifier(name) => self.define(name, Scheme::mono(expected.clone())),
+            Pattern::Literal(literal) => {
+                let literal = match literal {
+                    Expression::Integer(_) => Type::Integer,
+                    Expression::Float(_) => Type::Float,
+                    Expression::Bool(_) => Type::Bool,
+                    Expression::String(_) => Type::String,
+                    _ => Type::Any,
+                };
+                self.expect(expected, &literal, &node.position);
+            }
+            Pattern::Variant {
+                enumeration,
+                variant,
+                fields,
+            } => {
+                let (Expression::Identifier(name), Expression::Identifier(variant_name)) =
+                    (&enumeration.value, &variant.value)
+                else {
+                    return;
+                };
+                let Some(enum_type) = self.enum_type(name) else {
+                    for field in fields {
+                        self.check_pattern(field, &Type::Any);
+                    }
+                    return;
+                };
+                self.expect(expected, &enum_type, &node.position);
+                let Type::Enum(_, parameters) = enum_type else {
+                    unreachable!("enum_type returns an Enum");
+                };
+                let info = &self.enums[name];
+                let Some((_, indices)) = info.variants.iter().find(|(v, _)| v == variant_name)
+                else {
+                    self.error(
+                        format!("no variant `{variant_name}` on {name}"),
+                        &variant.position,
+                    );
+                    return;
+                };
+                if indices.len() != fields.len() {
+                    let message = format!(
+                        "variant {name}::{variant_name} has {} fields, pattern has {}",
+                        indices.len(),
+                        fields.len()
+                    );
+                    self.error(message, &node.position);
+                    return;
+                }
+                let field_types: Vec<Type> = indices
+                    .iter()
+                    .map(|index| parameters[*index].clone())
+                    .collect();
+                for (field, field_type) in fields.iter().zip(&field_types) {
+                    self.check_pattern(field, field_type);
+                }
+            }
+        }
+    }
+    fn member_type(&mut self, object: &Type, member: &Node<Expression>) -> Type {
+        let Expression::Identifier(name) = &member.value else {
+            return Type::Any;
+        };
+        match self.shallow(object) {
+            Type::Struct(struct_name, parameters) => {
+                let info = &self.structs[&struct_name];
+                if let Some(index) = info.fields.iter().position(|field| field == name) {
+                    return parameters[index].clone();
+                }
+                self.error(
+                    format!("no field `{name}` on {struct_name}"),
+                    &member.position,
+                );
+                Type::Any
+            }
+            Type::Map(element) => *element,
+            Type::Variable(_) | Type::Any => Type::Any,
+            object => {
+                let object = self.resolve(&object);
+                self.error(
+                    format!("cannot access member {name} on {object}"),
+                    &member.position,
+                );
+                Type::Any
+            }
+        }
+    }
+    fn method_type(&mut self, object: &Type, member: &Node<Expression>) -> Option<Type> {
+        let (Type::Struct(struct_name, _), Expression::Identifier(name)) =
+            (self.shallow(object), &member.value)
+        else {
+            return None;
+        };
+        let scheme = self.structs[&struct_name].methods.get(name)?.clone();
+        let method = self.instantiate(&scheme, &member.position);
+        let Type::Function(parameters, result) = self.shallow(&method) else {
+            return Some(method);
+        };
+        let Some((receiver, parameters)) = parameters.split_first() else {
+            return Some(Type::Any);
+        };
+        self.expect(receiver, object, &member.position);
+        Some(Type::Function(parameters.to_vec(), result))
+    }
+    fn check_call(&mut self, node: &Node<Expression>) -> Type {
+        let Expression::Call { subject, arguments } = &node.value else {
+            panic!("expected Call, got {:#?}", node.value);
+        };
+        let (callee, name) = match &subject.value {
+            Expression::Member {
+                subject: object,
+                member,
+            } => {
+                let object_type = self.infer(object);
+                match self.method_type(&object_type, member) {
+                    Some(method) => {
+                        let name = match (self.resolve(&object_type), &member.value) {
+                            (object, Expression::Identifier(member)) => {
+                                format!("function {object}.{member}")
+                            }
+                            _ => "function".to_string(),
+                        };
+                        (method, name)
+                    }
+                    None => (
+                        self.member_type(&object_type, member),
+                        "function".to_string(),
+                    ),
+                }
+            }
+            Expression::Identifier(name) => (self.infer(subject), format!("function {name}")),
+            _ => (self.infer(subject), "function".to_string()),
+        };
+        let argument_types: Vec<Type> = arguments
+            .iter()
+            .map(|argument| self.infer(argument))
+            .collect();
+
+        match self.shallow(&callee) {
+            Type::Function(parameters, result) => {
+                if parameters.len() != arguments.len() {
+                    self.error(
+                        format!(
+                            "{name} takes {} arguments, got {}",
+                            parameters.len(),
+                            arguments.len()
+                        ),
+                        &node.position,
+                    );
+                    return *result;
+                }
+                for ((parameter, argument), argument_type) in
+                    parameters.iter().zip(arguments).zip(&argument_types)
+                {
+                    self.expect(parameter, argument_type, &argument.position);
+                }
+                *result
+            }
+            Type::Variable(_) => {
+                let result = self.fresh();
+                let function = Type::Function(argument_types, Box::new(result.clone()));
+                self.expect(&callee, &function, &subject.position);
+                result
+            }
+            Type::Any => Type::Any,
+            callee => {
+                let callee = self.resolve(&callee);
+                self.error(
+                    format!("expected function, got {callee}"),
+                    &subject.position,
+                );
+                Type::Any
+            }
+        }
+    }
+    fn infer(&mut self, node: &Node<Expression>) -> Type {
+        match &node.value {
+            Expression::Integer(_) => Type::Integer,
+            Expression::Float(_) => Type::Float,
+            Expression::Bool(_) => Type::Bool,
+            Expression::String(_) => Type::String,
+            Expression::Identifier(name) => match self.lookup(name).cloned() {
+                Some(scheme) => self.instantiate(&scheme, &node.position),
+                None => Type::Any,
+            },
+            Expression::Call { .. } => self.check_call(node),
+            Expression::List(elements) => {
+                let values: Vec<Type> = elements.iter().map(|node| self.infer(node)).collect();
+                Type::List(Box::new(self.common(&values)))
+            }
+            Expression::Map(entries) => {
+                let mut values = Vec::new();
+                for (key, value) in entries {
+                    let key_type = self.infer(key);
+                    self.expect(&Type::String, &key_type, &key.position);
+                    values.push(self.infer(value));
+                }
+                Type::Map(Box::new(self.common(&values)))
+            }
+            Expression::Index { subject, index } => {
+                let subject_type = self.infer(subject);
+                let index_type = self.infer(index);
+                match (self.shallow(&subject_type), self.shallow(&index_type)) {
+                    (Type::List(element), _) => {
+                        self.expect(&Type::Integer, &index_type, &index.position);
+                        *element
+                    }
+                    (Type::Map(element), _) => {
+                        self.expect(&Type::String, &index_type, &index.position);
+                        *element
+                    }
+                    (Type::Variable(_), Type::Integer) => {
+                        let element = self.fresh();
+                        let list = Type::List(Box::new(element.clone()));
+                        self.expect(&list, &subject_type, &subject.position);
+                        element
+                    }
+                    (Type::Variable(_), Type::String) => {
+                        let element = self.fresh();
+                        let map = Type::Map(Box::new(element.clone()));
+                        self.expect(&map, &subject_type, &subject.position);
+                        element
+                    }
+                    (Type::Variable(_) | Type::Any, _) => Type::Any,
+                    (subject_type, _) => {
+                        let subject_type = self.resolve(&subject_type);
+                        self.error(
+                            format!("cannot index into {subject_type}"),
+                            &subject.position,
+                        );
+                        Type::Any
+                    }
+                }
+            }
+            Expression::Member { subject, member } => {
+                let subject_type = self.infer(subject);
+                self.member_type(&subject_type, member)
+            }
+            Expression::Struct { identifier, fields } => {
+                let Expression::Identifier(name) = &identifier.value else {
+                    return Type::Any;
+                };
+                let Some(struct_type) = self.struct_type(name) else {
+                    for (_, value) in fields {
+                        self.infer(value);
+                    }
+                    return Type::Any;
+                };
+                let Type::Struct(_, parameters) = &struct_type else {
+                    unreachable!("struct_type returns a Struct");
+                };
+                let declared = self.structs[name].fields.clone();
+                for (field, value) in fields {
+                    let value_type = self.infer(value);
+                    let Expression::Identifier(field_name) = &field.value else {
+                        continue;
+                    };
+                    match declared.iter().position(|other| other == field_name) {
+                        Some(index) => {
+                            self.expect(&parameters[index], &value_type, &value.position);
+                        }
+                        None => self.error(
+                            format!("no field `{field_name}` on {name}"),
+                            &field.position,
+                        ),
+                    }
+                }
+                for field_name in &declared {
+                    let given = fields.iter().any(|(field, _)| {
+                        field.value == Expression::Identifier(field_name.clone())
+                    });
+                    if !given {
+                        self.error(
+                            format!("missing field `{field_name}` in {name} literal"),
+                            &node.position,
+                        );
+                    }
+                }
+                struct_type
+            }
+            Expression::Path { subject, member } => {
+                let (Expression::Identifier(name), Expression::Identifier(variant)) =
+                    (&subject.value, &member.value)
+                else {
+                    return Type::Any;
+                };
+                let Some(enum_type) = self.enum_type(name) else {
+                    return Type::Any;
+                };
+                let Type::Enum(_, parameters) = &enum_type else {
+                    unreachable!("enum_type returns an Enum");
+                };
+                let info = &self.enums[name];
+                let Some((_, indices)) = info.variants.iter().find(|(other, _)| other == variant)
+                else {
+                    self.error(
+                        format!("no variant `{variant}` on {name}"),
+                        &member.position,
+                    );
+                    return Type::Any;
+                };
+                if indices.is_empty() {
+                    return enum_type;
+                }
+                let fields = indices
+                    .iter()
+                    .map(|index| parameters[*index].clone())
+                    .collect();
+                Type::Function(fields, Box::new(enum_type))
+            }
+            Expression::Match { subject, arms } => {
+                let subject_type = self.infer(subject);
+                let result = self.fresh();
+                let mut consistent = true;
+                for MatchArm {
+                    pattern,
+                    guard,
+                    body,
+                } in arms
+                {
+                    self.scopes.push(HashMap::new());
+                    self.check_pattern(pattern, &subject_type);
+                    if let Some(guard) = guard {
+                        let guard_type = self.infer(guard);
+                        self.expect(&Type::Bool, &guard_type, &guard.position);
+                    }
+                    let body_type = self.infer(body);
+                    consistent &= self.unify(&result, &body_type).is_ok();
+                    self.scopes.pop();
+                }
+                if consistent {
+                    result
+                } else {
+                    Type::Any
+                }
+            }
+            Expression::Function {
+                parameters,
+                return_type,
+                body,
+            } => self.check_function(
+                "anonymous function",
+                parameters,
+                return_type.as_deref(),
+                body,
+                None,
+                &node.position,
+            ),
+            Expression::Block(statements) => {
+                self.scopes.push(HashMap::new());
+                let mut last = Type::Unit;
+                for statement in statements {
+                    last = self.check_statement(statement);
+                }
+                self.scopes.pop();
+                last
+            }
+            Expression::If {
+                condition,
+                truthy,
+                falsy,
+            } => {
+                let condition_type = self.infer(condition);
+                self.expect(&Type::Bool, &condition_type, &condition.position);
+                let truthy_type = self.infer(truthy);
+                let Some(falsy) = falsy else {
+                    return Type::Unit;
+                };
+                let falsy_type = self.infer(falsy);
+                match self.unify(&truthy_type, &falsy_type) {
+                    Ok(()) => truthy_type,
+                    Err(_) => Type::Any,
+                }
+            }
+            Expression::Unary { subject, variant } => {
+                let subject_type = self.infer(subject);
+                match variant {
+                    UnaryVariant::NegateNumber => {
+                        self.require(&subject_type, NUMBERS, Requirement::Number, &node.position);
+                        subject_type
+                    }
+                    UnaryVariant::NegateBool => {
+                        self.expect(&Type::Bool, &subject_type, &node.position);
+                        Type::Bool
+                    }
+                }
+            }
+            Expression::Binary {
+                left,
+                right,
+                variant,
+            } => self.check_binary(node, left, right, variant),
+            Expression::Error(_) => Type::Any,
+        }
+    }
+    fn check_binary(
+        &mut self,
+        node: &Node<Expression>,
+        left: &Node<Expression>,
+        right: &Node<Expression>,
+        variant: &BinaryVariant,
+    ) -> Type {
+        let left_type = self.infer(left);
+        let right_type = self.infer(right);
+        let operator = operator(variant);
+        let (operands, allowed, result): (Type, &'static [Type], Option<Type>) = match variant {
+            BinaryVariant::And | BinaryVariant::Or => {
+                self.expect(&Type::Bool, &left_type, &left.position);
+                self.expect(&Type::Bool, &right_type, &right.position);
+                return Type::Bool;
+            }
+            BinaryVariant::Addition => (left_type.clone(), ADDABLE, None),
+            BinaryVariant::Subtraction
+            | BinaryVariant::Multiplication
+            | BinaryVariant::Division
+            | BinaryVariant::Modulo
+            | BinaryVariant::Exponentiation => (left_type.clone(), NUMBERS, None),
+            BinaryVariant::BitwiseAnd
+            | BinaryVariant::BitwiseOr
+            | BinaryVariant::BitwiseXor
+            | BinaryVariant::LeftShift
+            | BinaryVariant::RightShift => (Type::Integer, &[], Some(Type::Integer)),
+            BinaryVariant::Less
+            | BinaryVariant::LessEqual
+            | BinaryVariant::Greater
+            | BinaryVariant::GreaterEqual => (left_type.clone(), NUMBERS, Some(Type::Bool)),
+            BinaryVariant::Equal | BinaryVariant::NotEqual => {
+                (left_type.clone(), &[], Some(Type::Bool))
+            }
+        };
+        let unified =
+            self.unify(&operands, &left_type).is_ok() && self.unify(&operands, &right_type).is_ok();
+        if !unified {
+            let (left_type, right_type) = (self.resolve(&left_type), self.resolve(&right_type));
+            self.error(
+                format!("no implementation exists for {left_type} {operator} {right_type}"),
+                &node.position,
+            );
+            return result.unwrap_or(Type::Any);
+        }
+        if !allowed.is_empty() {
+            let requirement = Requirement::Operator(operator.to_string());
+            self.require(&operands, allowed, requirement, &node.position);
+        }
+        result.unwrap_or(operands)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use couch_lang_evaluator::{value::Value, Evaluator, IdentifierType};
+    use couch_lang_lexer::Lexer;
+    use couch_lang_parser::Parser;
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn parse(input: &str) -> Vec<Node<Statement>> {
+        let lexer = Lexer::new(input.chars());
+        Parser::new(lexer.into_iter(), input.to_string()).parse_statements()
+    }
+
+    fn errors(input: &str) -> Vec<(String, usize, usize)> {
+        check(&parse(input))
+            .into_iter()
+            .map(|error| (error.message, error.position.line, error.position.column))
+            .collect()
+    }
+
+    type Case<'a> = (&'a str, Vec<(&'a str, usize, usize)>);
+
+    fn assert_errors(cases: Vec<Case>) {
+        for (input, expected) in cases {
+            let expected: Vec<(String, usize, usize)> = expected
+                .into_iter()
+                .map(|(message, line, column)| (message.to_string(), line, column))
+                .collect();
+            assert_eq!(expected, errors(input), "{input}");
+        }
+    }
+
+    #[test]
+    fn well_typed_programs() {
+        let cases = [
+            "let x: int = 5; let y = x + 2; let z: float = 1.5 * 2.0;",
+            "let s = \"a\" + \"b\"; let b: bool = !(1 < 2) || s == \"ab\";",
+            "fn add(a: int, b: int) -> int { return a + b; } add(1, 2);",
+            "fn id(x) { return x; } let a: int = id(1); let b: string = id(\"b\");",
+            "fn fact(n) { if n < 2 { return 1; } return n * fact(n - 1); } fact(5) + 1;",
+            "let mut xs = [1, 2]; push(xs, 3); xs[0] = pop(xs); let n: int = len(xs);",
+            "let m = {\"a\": 1}; m[\"b\"] = 2; let n: int = m.a + m[\"b\"];",
+            "let mixed = {\"a\": 1, \"b\": [2]}; mixed.c = [1, \"a\"];",
+            "let f = fn(g: fn(int) -> int) -> int { return g(1); }; f(fn(x) { return x * 2; });",
+            "struct Point { x, y }\nimpl Point { fn len(self) { return self.x * self.x + self.y * self.y; } }\nlet p = Point { x: 1, y: 2 }; let l: int = p.len();",
+            "enum Shape { Circle(radius), Empty }\nfn area(s) { return match s { Shape::Circle(r) => r * r, Shape::Empty => 0 }; }\narea(Shape::Circle(2)) + 1;",
+            "let mut i = 0; while i < 10 { i += 1; if i == 5 { break; } }",
+            "fn loop_forever() { while true {} } let x = if true { 1; } else { \"mixed\"; };",
+            "let a: integer = abs(-1) + min(1, 2); let b: float = max(abs(-1.5), 2.0);",
+        ];
+        for input in cases {
+            assert_eq!(
+                Vec::<(String, usize, usize)>::new(),
+                errors(input),
+                "{input}"
+            );
+        }
+    }
+
+    #[test]
+    fn type_errors() {
+        let cases = [
+            (
+                "let x: int = 1.5;",
+                vec![("expected integer, got float", 1, 14)],
+            ),
+            (
+                "1 + \"a\";",
+                vec![("no implementation exists for integer + string", 1, 1)],
+            ),
+            (
+                "fn f(a: float) -> bool { return a; }",
+                vec![("expected bool, got float", 1, 33)],
+            ),
+            (
+                "fn f(a: int) {} f(true);",
+                vec![("expected integer, got bool", 1, 19)],
+            ),
+            (
+                "fn f(a) -> int { if a { return 1; } }",
+                vec![("function f must return integer on every path", 1, 1)],
+            ),
+            (
+                "fn f(a, b) {} f(1);",
+                vec![("function f takes 2 arguments, got 1", 1, 15)],
+            ),
+            (
+                "let x = 5; x();",
+                vec![("expected function, got integer", 1, 12)],
+            ),
+            ("!5;", vec![("expected bool, got integer", 1, 1)]),
+            ("-true;", vec![("expected number, got bool", 1, 1)]),
+            (
+                "true * false;",
+                vec![("no implementation exists for bool * bool", 1, 1)],
+            ),
+            ("5[0];", vec![("cannot index into integer", 1, 1)]),
+            (
+                "let m = {\"a\": 1}; m[0];",
+                vec![("expected string, got integer", 1, 21)],
+            ),
+            (
+                "let x: [int] = [1.5];",
+                vec![("expected [integer], got [float]", 1, 16)],
+            ),
+            ("let x: Thing = 1;", vec![("unknown type Thing", 1, 8)]),
+            (
+                "struct P { x }\nlet p = P { x: 1 }; p.y;",
+                vec![("no field `y` on P", 2, 23)],
+            ),
+            (
+                "struct P { x }\nlet p = P { x: 1 }; p.x = \"a\";",
+                vec![("expected integer, got string", 2, 27)],
+            ),
+            (
+                "enum E { A(x), B }\nmatch E::A(1) { E::A(\"s\") => 1, E::B => 2 };",
+                vec![("expected integer, got string", 2, 22)],
+            ),
+            (
+                "enum E { A, B }\nE::C;",
+                vec![("no variant `C` on E", 2, 4)],
+            ),
+            (
+                "let mut s = \"a\"; s -= \"b\";",
+                vec![("no implementation exists for string -= string", 1, 18)],
+            ),
+            ("while 1 {}", vec![("expected bool, got integer", 1, 7)]),
+            ("abs(\"x\");", vec![("expected number, got string", 1, 1)]),
+            (
+                "min([1], [2]);",
+                vec![("expected number, got [integer]", 1, 1)],
+            ),
+            (
+                "let a: int = \"x\";\nlet b: string = 2;\nfn f() -> bool { return 1; }",
+                vec![
+                    ("expected integer, got string", 1, 14),
+                    ("expected string, got integer", 2, 17),
+                    ("expected bool, got integer", 3, 25),
+                ],
+            ),
+        ];
+        assert_errors(cases.into());
+    }
+
+    #[test]
+    fn operators() {
+        assert_errors(vec![
+            (
+                "let a: bool = 1 < 2; let b: int = 1 << 2 | 3; let c: float = 2.0 ** 0.5;",
+                vec![],
+            ),
+            (
+                "let s: string = \"a\" + \"b\"; let t: bool = s != \"c\";",
+                vec![],
+            ),
+            (
+                "1 + 2.0;",
+                vec![("no implementation exists for integer + float", 1, 1)],
+            ),
+            (
+                "\"a\" - \"b\";",
+                vec![("no implementation exists for string - string", 1, 1)],
+            ),
+            (
+                "1.5 & 2;",
+                vec![("no implementation exists for float & integer", 1, 1)],
+            ),
+            (
+                "1 == \"a\";",
+                vec![("no implementation exists for integer == string", 1, 1)],
+            ),
+            (
+                "\"a\" < \"b\";",
+                vec![("no implementation exists for string < string", 1, 1)],
+            ),
+            ("true && 1;", vec![("expected bool, got integer", 1, 9)]),
+            ("-\"a\";", vec![("expected number, got string", 1, 1)]),
+            (
+                "let mut f = 1.5; f += 1;",
+                vec![("no implementation exists for float += integer", 1, 18)],
+            ),
+            (
+                "let mut b = true; b *= false;",
+                vec![("no implementation exists for bool *= bool", 1, 19)],
+            ),
+        ]);
+    }
+
+    #[test]
+    fn returns() {
+        assert_errors(vec![
+            (
+                "fn maybe(b) { if b { return 1; } return 2.5; }\nlet s: string = maybe(true);",
+                vec![],
+            ),
+            (
+                "fn f(a) { if a { return [1]; } }\nlet s: string = f(true);",
+                vec![],
+            ),
+            ("fn f() { return; }\nlet u: unit = f();", vec![]),
+            (
+                "fn maybe(b) -> int { if b { return 1; } return 2.5; }",
+                vec![("expected integer, got float", 1, 48)],
+            ),
+            (
+                "fn f() -> unit { return 1; }",
+                vec![("expected unit, got integer", 1, 25)],
+            ),
+            (
+                "fn f(a) { return a; }\nlet s: string = f(1);",
+                vec![("expected string, got integer", 2, 17)],
+            ),
+        ]);
+        let mut checker = TypeChecker::new();
+        let source = "fn maybe(b) { if b { return 1; } return 2.5; }";
+        assert_eq!(Vec::<TypeError>::new(), checker.check(&parse(source)));
+        assert_eq!(
+            Some(Type::Function(vec![Type::Bool], Box::new(Type::Any))),
+            checker.type_of("maybe")
+        );
+    }
+
+    #[test]
+    fn generalization() {
+        assert_errors(vec![
+            (
+                "fn id(x) { return x; }\nlet f = id;\nlet a: int = f(1);\nlet b: string = f(\"b\");",
+                vec![],
+            ),
+            (
+                "let f = fn(x) { return x; };\nlet a: int = f(1);\nlet b: string = f(\"b\");",
+                vec![],
+            ),
+            (
+                "enum Option { Some(value), None }\nlet some = Option::Some;\nsome(1);\nsome(\"a\");",
+                vec![],
+            ),
+            (
+                "fn pair(x) { let y = x; return [y]; }\nlet a: [int] = pair(1);\nlet b: [string] = pair(\"b\");",
+                vec![],
+            ),
+            (
+                "fn apply(g) { g(1); g(\"a\"); }",
+                vec![("expected integer, got string", 1, 23)],
+            ),
+            (
+                "let mut xs = [];\nlet mut ys = xs;\npush(ys, 1);\nlet s: string = xs[0];",
+                vec![("expected string, got integer", 4, 17)],
+            ),
+        ]);
+    }
+
+    #[test]
+    fn containers() {
+        assert_errors(vec![
+            (
+                "let xs = [1, \"a\"];\nlet s: string = xs[0];\nlet n: int = xs[1];",
+                vec![],
+            ),
+            (
+                "let m = {\"a\": 1, \"b\": \"c\"};\nlet n: int = m.b;",
+                vec![],
+            ),
+            (
+                "let k: [string] = keys({\"a\": 1});\nlet n: int = pop([1]) + remove({\"a\": 1}, \"a\");",
+                vec![],
+            ),
+            ("let mut xs: [int] = [];\npush(xs, 1);", vec![]),
+            (
+                "let mut xs = [1];\npush(xs, \"a\");",
+                vec![("expected integer, got string", 2, 10)],
+            ),
+            (
+                "let mut xs = [1];\nxs[0] = \"a\";",
+                vec![("expected integer, got string", 2, 9)],
+            ),
+            (
+                "let mut m = {\"a\": 1};\nm.b = true;",
+                vec![("expected integer, got bool", 2, 7)],
+            ),
+            (
+                "let xs = [1];\nxs[\"a\"];",
+                vec![("expected integer, got string", 2, 4)],
+            ),
+            (
+                "has([1], \"a\");",
+                vec![("expected {any}, got [integer]", 1, 5)],
+            ),
+        ]);
+    }
+
+    #[test]
+    fn checker_can_be_rolled_back() {
+        let mut checker = TypeChecker::new();
+        let checkpoint = checker.clone();
+        assert_eq!(Vec::<TypeError>::new(), checker.check(&parse("let x = 5;")));
+        assert_eq!(Some(Type::Integer), checker.type_of("x"));
+        checker = checkpoint;
+        assert_eq!(None, checker.type_of("x"));
+    }
+
+    #[test]
+    fn checker_keeps_definitions_between_checks() {
+        let mut checker = TypeChecker::new();
+        assert_eq!(Vec::<TypeError>::new(), checker.check(&parse("let x = 5;")));
+        assert_eq!(Some(Type::Integer), checker.type_of("x"));
+        assert_eq!(1, checker.check(&parse("let y = 1; x + true;")).len());
+        assert_eq!(None, checker.type_of("y"));
+    }
+
+    #[test]
+    fn every_native_has_a_signature() {
+        let checker = TypeChecker::new();
+        for (name, definition) in Evaluator::new().natives() {
+            let IdentifierType::Value {
+                value: Value::NativeFunction(native),
+                ..
+            } = definition
+            else {
+                panic!("{name} is not a native function");
+            };
+            match checker.type_of(&name) {
+                Some(Type::Function(parameters, _)) => {
+                    assert_eq!(native.arity, parameters.len(), "{name}")
+                }
+                signature => panic!("{name} has signature {signature:?}"),
+            }
+        }
+    }
+}
@@ -0,0 +1,42 @@
+use crate::{types::Type, TypeChecker};
+
+fn list(element: Type) -> Type {
+    Type::List(Box::new(element))
+}
+
+fn map(element: Type) -> Type {
+    Type::Map(Box::new(element))
+}
+
+pub(crate) fn register_prelude(checker: &mut TypeChecker) {
+    checker.register("int", vec![Type::Any], Type::Integer);
+    checker.register("float", vec![Type::Any], Type::Float);
+    checker.register("print", vec![Type::Any], Type::Unit);
+    checker.register("println", vec![Type::Any], Type::Unit);
+    checker.register("exit", vec![Type::Integer], Type::Unit);
+    checker.register("type_of", vec![Type::Any], Type::String);
+    checker.register("assert", vec![Type::Bool], Type::Unit);
+
+    let a = checker.fresh();
+    checker.register("assert_eq", vec![a.clone(), a], Type::Unit);
+
+    checker.register("len", vec![Type::Any], Type::Integer);
+    let a = checker.fresh();
+    checker.register("push", vec![list(a.clone()), a], Type::Unit);
+    let a = checker.fresh();
+    checker.register("pop", vec![list(a.clone())], a);
+
+    checker.register("keys", vec![map(Type::Any)], list(Type::String));
+    checker.register("has", vec![map(Type::Any), Type::String], Type::Bool);
+    let a = checker.fresh();
+    checker.register("remove", vec![map(a.clone()), Type::String], a);
+
+    let a = checker.number();
+    checker.register("min", vec![a.clone(), a.clone()], a);
+    let a = checker.number();
+    checker.register("max", vec![a.clone(), a.clone()], a);
+    let a = checker.number();
+    checker.register("abs", vec![a.clone()], a);
+    checker.register("sqrt", vec![Type::Float], Type::Float);
+    checker.register("floor", vec![Type::Float], Type::Float);
+}
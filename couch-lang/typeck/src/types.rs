@@ -0,0 +1,80 @@
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Type {
+    Integer,
+    Float,
+    Bool,
+    String,
+    Unit,
+    Any,
+    List(Box<Type>),
+    Map(Box<Type>),
+    Function(Vec<Type>, Box<Type>),
+    Struct(String, Vec<Type>),
+    Enum(String, Vec<Type>),
+    Variable(usize),
+}
+
+impl Type {
+    pub(crate) fn variables(&self, variables: &mut Vec<usize>) {
+        match self {
+            Type::Variable(variable) => {
+                if !variables.contains(variable) {
+                    variables.push(*variable);
+                }
+            }
+            Type::List(element) | Type::Map(element) => element.variables(variables),
+            Type::Function(parameters, result) => {
+                for parameter in parameters {
+                    parameter.variables(variables);
+                }
+                result.variables(variables);
+            }
+            Type::Struct(_, parameters) | Type::Enum(_, parameters) => {
+                for parameter in parameters {
+                    parameter.variables(variables);
+                }
+            }
+            Type::Integer | Type::Float | Type::Bool | Type::String | Type::Unit | Type::Any => {}
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Type::Integer => f.write_str("integer"),
+            Type::Float => f.write_str("float"),
+            Type::Bool => f.write_str("bool"),
+            Type::String => f.write_str("string"),
+            Type::Unit => f.write_str("unit"),
+            Type::Any => f.write_str("any"),
+            Type::List(element) => write!(f, "[{element}]"),
+            Type::Map(element) => write!(f, "{{{element}}}"),
+            Type::Function(parameters, result) => {
+                let parameters: Vec<String> = parameters.iter().map(Type::to_string).collect();
+                write!(f, "fn({}) -> {result}", parameters.join(", "))
+            }
+            Type::Struct(name, _) | Type::Enum(name, _) => f.write_str(name),
+            Type::Variable(_) => f.write_str("?"),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Scheme {
+    pub variables: Vec<usize>,
+    pub numeric: Vec<usize>,
+    pub value: Type,
+}
+
+impl Scheme {
+    pub fn mono(value: Type) -> Self {
+        Self {
+            variables: Vec::new(),
+            numeric: Vec::new(),
+            value,
+        }
+    }
+}